@@ -0,0 +1,72 @@
+use std::fs;
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use serde_derive::{Deserialize, Serialize};
+
+use bitrust_bencode::{from_slice, to_vec, Value};
+
+const FIXTURE: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/tests/data/ubuntu-19.10-desktop-amd64.iso.torrent"
+);
+
+#[derive(Deserialize, Serialize)]
+struct Info<'a> {
+    length: u64,
+    name: &'a str,
+    #[serde(rename = "piece length")]
+    piece_length: u64,
+    #[serde(with = "serde_bytes")]
+    pieces: &'a [u8],
+}
+
+#[derive(Deserialize, Serialize)]
+struct Torrent<'a> {
+    announce: &'a str,
+    #[serde(rename = "announce-list")]
+    announce_list: Vec<Vec<&'a str>>,
+    comment: &'a str,
+    #[serde(rename = "creation date")]
+    creation_date: i64,
+    #[serde(borrow)]
+    info: Info<'a>,
+}
+
+/// A message with many small integers & strings, such as a DHT response.
+fn integers() -> Vec<Value> {
+    (0..1000i64)
+        .map(|i| Value::List(vec![Value::from(i * 7919), Value::from("id")]))
+        .collect()
+}
+
+fn deserialize(c: &mut Criterion) {
+    let data = fs::read(FIXTURE).unwrap();
+
+    let mut group = c.benchmark_group("deserialize");
+    group.throughput(Throughput::Bytes(data.len() as u64));
+    group.bench_function("struct", |b| {
+        b.iter(|| from_slice::<Torrent>(&data).unwrap())
+    });
+    group.bench_function("value", |b| b.iter(|| from_slice::<Value>(&data).unwrap()));
+    group.finish();
+}
+
+fn serialize(c: &mut Criterion) {
+    let data = fs::read(FIXTURE).unwrap();
+    let torrent = from_slice::<Torrent>(&data).unwrap();
+    let value = from_slice::<Value>(&data).unwrap();
+    let integers = integers();
+
+    let mut group = c.benchmark_group("serialize");
+    group.throughput(Throughput::Bytes(data.len() as u64));
+    group.bench_function("struct", |b| b.iter(|| to_vec(&torrent).unwrap()));
+    group.bench_function("value", |b| b.iter(|| to_vec(&value).unwrap()));
+    group.finish();
+
+    c.bench_function("serialize/integers", |b| {
+        b.iter(|| to_vec(&integers).unwrap())
+    });
+}
+
+criterion_group!(benches, deserialize, serialize);
+criterion_main!(benches);
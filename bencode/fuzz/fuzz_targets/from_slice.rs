@@ -0,0 +1,15 @@
+//! Deserializes arbitrary input, that must never panic.
+
+#![no_main]
+
+use bitrust_bencode::{document::Document, from_slice, RawValue, Value};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = from_slice::<Value>(data);
+    let _ = from_slice::<RawValue>(data);
+
+    if let Ok(document) = Document::new(data) {
+        let _ = document.root().len();
+    }
+});
@@ -0,0 +1,20 @@
+//! Serializes the deserialized input back, that must reproduce canonical
+//! input byte for byte & always be canonical itself.
+
+#![no_main]
+
+use bitrust_bencode::{from_slice, from_slice_strict, to_vec, Value};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(value) = from_slice::<Value>(data) else {
+        return;
+    };
+
+    let encoded = to_vec(&value).unwrap();
+    assert_eq!(value, from_slice_strict::<Value>(&encoded).unwrap());
+
+    if from_slice_strict::<Value>(data).is_ok() {
+        assert_eq!(data, encoded.as_slice());
+    }
+});
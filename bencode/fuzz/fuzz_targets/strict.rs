@@ -0,0 +1,17 @@
+//! Compares the strict mode with the lenient one. The strict mode must
+//! reject any input, that is not canonical, & accept nothing, that the
+//! lenient mode rejects.
+
+#![no_main]
+
+use bitrust_bencode::{from_slice, from_slice_strict, to_vec, Value};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    match (from_slice::<Value>(data), from_slice_strict::<Value>(data)) {
+        (Ok(lenient), Ok(strict)) => assert_eq!(lenient, strict),
+        (Ok(lenient), Err(_)) => assert_ne!(data, to_vec(&lenient).unwrap().as_slice()),
+        (Err(_), Ok(_)) => panic!("accepted only in the strict mode"),
+        (Err(_), Err(_)) => {}
+    }
+});
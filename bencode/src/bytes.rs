@@ -0,0 +1,268 @@
+//! Serde adapters for fixed-size binary values packed into byte strings.
+//!
+//! BitTorrent packs hashes, IDs & addresses into byte strings, either one
+//! value per byte string (e.g. the 20 byte info-hash) or many values
+//! concatenated into a single byte string (e.g. the compact peer list of a
+//! tracker response, or the `pieces` of a torrent). Without these adapters,
+//! `serde` would (de)serialize an array as a Bencode list.
+//!
+//! The adapters are used with `#[serde(with = "...")]`:
+//!
+//! - `bytes::fixed` for a single value, e.g. `[u8; 20]` or `SocketAddrV4`,
+//! - `bytes::chunks` for a `Vec` of values, e.g. `Vec<[u8; 20]>` or
+//!   `Vec<SocketAddrV4>`.
+//!
+//! The values are any types implementing `Compact`, which includes bytes,
+//! byte arrays, the compact peer encodings of `SocketAddrV4` (6 bytes) &
+//! `SocketAddrV6` (18 bytes), and pairs of them, e.g. the compact node info
+//! `([u8; 20], SocketAddrV4)` (26 bytes). A byte string of a wrong length
+//! results in an error.
+
+use alloc::vec::Vec;
+use core::{
+    fmt,
+    marker::PhantomData,
+    net::{Ipv4Addr, Ipv6Addr, SocketAddrV4, SocketAddrV6},
+};
+
+use serde::de;
+
+/// A value with a fixed-size binary encoding.
+pub trait Compact: Sized {
+    /// Number of bytes of the encoding.
+    const LENGTH: usize;
+
+    /// Appends the encoding of the value to the output.
+    fn encode(&self, output: &mut Vec<u8>);
+
+    /// Decodes the value from exactly `LENGTH` bytes.
+    fn decode(bytes: &[u8]) -> Self;
+}
+
+/// A single byte, so that `bytes::chunks` packs a `Vec<u8>` into a byte
+/// string.
+impl Compact for u8 {
+    const LENGTH: usize = 1;
+
+    fn encode(&self, output: &mut Vec<u8>) {
+        output.push(*self);
+    }
+
+    fn decode(bytes: &[u8]) -> Self {
+        bytes[0]
+    }
+}
+
+impl<const N: usize> Compact for [u8; N] {
+    const LENGTH: usize = N;
+
+    fn encode(&self, output: &mut Vec<u8>) {
+        output.extend_from_slice(self);
+    }
+
+    fn decode(bytes: &[u8]) -> Self {
+        let mut array = [0; N];
+        array.copy_from_slice(bytes);
+        array
+    }
+}
+
+/// The IP address followed by the port, both in the network byte order.
+impl Compact for SocketAddrV4 {
+    const LENGTH: usize = 6;
+
+    fn encode(&self, output: &mut Vec<u8>) {
+        output.extend_from_slice(&self.ip().octets());
+        output.extend_from_slice(&self.port().to_be_bytes());
+    }
+
+    fn decode(bytes: &[u8]) -> Self {
+        let ip = <[u8; 4]>::decode(&bytes[..4]);
+        let port = <[u8; 2]>::decode(&bytes[4..]);
+
+        SocketAddrV4::new(Ipv4Addr::from(ip), u16::from_be_bytes(port))
+    }
+}
+
+/// The IP address followed by the port, both in the network byte order.
+/// The flow info & the scope ID are not encoded.
+impl Compact for SocketAddrV6 {
+    const LENGTH: usize = 18;
+
+    fn encode(&self, output: &mut Vec<u8>) {
+        output.extend_from_slice(&self.ip().octets());
+        output.extend_from_slice(&self.port().to_be_bytes());
+    }
+
+    fn decode(bytes: &[u8]) -> Self {
+        let ip = <[u8; 16]>::decode(&bytes[..16]);
+        let port = <[u8; 2]>::decode(&bytes[16..]);
+
+        SocketAddrV6::new(Ipv6Addr::from(ip), u16::from_be_bytes(port), 0, 0)
+    }
+}
+
+/// Both of the values concatenated, e.g. a node ID & its address.
+impl<A, B> Compact for (A, B)
+where
+    A: Compact,
+    B: Compact,
+{
+    const LENGTH: usize = A::LENGTH + B::LENGTH;
+
+    fn encode(&self, output: &mut Vec<u8>) {
+        self.0.encode(output);
+        self.1.encode(output);
+    }
+
+    fn decode(bytes: &[u8]) -> Self {
+        let (a, b) = bytes.split_at(A::LENGTH);
+
+        (A::decode(a), B::decode(b))
+    }
+}
+
+//////////////////////////////////////////////////////
+
+/// Serde adapter of a single `Compact` value, encoded as a byte string.
+pub mod fixed {
+    use serde::{de, ser};
+
+    use super::{BytesVisitor, Compact, Fixed};
+    use alloc::vec::Vec;
+
+    pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: Compact,
+        S: ser::Serializer,
+    {
+        let mut bytes = Vec::with_capacity(T::LENGTH);
+        value.encode(&mut bytes);
+
+        serializer.serialize_bytes(&bytes)
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: Compact,
+        D: de::Deserializer<'de>,
+    {
+        deserializer.deserialize_bytes(BytesVisitor::<T, Fixed>::new())
+    }
+}
+
+/// Serde adapter of a `Vec` of `Compact` values, concatenated into a single
+/// byte string.
+pub mod chunks {
+    use serde::{de, ser};
+
+    use super::{BytesVisitor, Chunks, Compact};
+    use alloc::vec::Vec;
+
+    pub fn serialize<T, S>(values: &[T], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: Compact,
+        S: ser::Serializer,
+    {
+        let mut bytes = Vec::with_capacity(values.len() * T::LENGTH);
+
+        for value in values {
+            value.encode(&mut bytes);
+        }
+
+        serializer.serialize_bytes(&bytes)
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<Vec<T>, D::Error>
+    where
+        T: Compact,
+        D: de::Deserializer<'de>,
+    {
+        deserializer.deserialize_bytes(BytesVisitor::<T, Chunks>::new())
+    }
+}
+
+//////////////////////////////////////////////////////
+
+/// Shape of the value decoded by `BytesVisitor`.
+trait Shape<T: Compact> {
+    type Output;
+
+    fn expecting(formatter: &mut fmt::Formatter) -> fmt::Result;
+
+    fn decode(bytes: &[u8]) -> Option<Self::Output>;
+}
+
+struct Fixed;
+
+impl<T: Compact> Shape<T> for Fixed {
+    type Output = T;
+
+    fn expecting(formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "a byte string of {} bytes", T::LENGTH)
+    }
+
+    fn decode(bytes: &[u8]) -> Option<T> {
+        (bytes.len() == T::LENGTH).then(|| T::decode(bytes))
+    }
+}
+
+struct Chunks;
+
+impl<T: Compact> Shape<T> for Chunks {
+    type Output = Vec<T>;
+
+    fn expecting(formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            formatter,
+            "a byte string of a multiple of {} bytes",
+            T::LENGTH
+        )
+    }
+
+    fn decode(bytes: &[u8]) -> Option<Vec<T>> {
+        if T::LENGTH == 0 || !bytes.len().is_multiple_of(T::LENGTH) {
+            return None;
+        }
+
+        Some(bytes.chunks_exact(T::LENGTH).map(T::decode).collect())
+    }
+}
+
+struct BytesVisitor<T, S> {
+    output: PhantomData<fn() -> (T, S)>,
+}
+
+impl<T, S> BytesVisitor<T, S> {
+    fn new() -> Self {
+        Self {
+            output: PhantomData,
+        }
+    }
+}
+
+impl<T, S> de::Visitor<'_> for BytesVisitor<T, S>
+where
+    T: Compact,
+    S: Shape<T>,
+{
+    type Value = S::Output;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        S::expecting(formatter)
+    }
+
+    fn visit_bytes<E>(self, value: &[u8]) -> Result<S::Output, E>
+    where
+        E: de::Error,
+    {
+        S::decode(value).ok_or_else(|| E::invalid_length(value.len(), &self))
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<S::Output, E>
+    where
+        E: de::Error,
+    {
+        self.visit_bytes(value.as_bytes())
+    }
+}
@@ -0,0 +1,137 @@
+//! Framing of Bencode values for `tokio` streams.
+
+use std::{fmt, marker::PhantomData};
+
+use crate::{
+    de::{from_slice_with_options, DeserializerOptions},
+    error::Error,
+    push::{Scanner, Status},
+    ser::{to_writer_with_options, SerializerOptions},
+    value::Value,
+};
+
+use bytes::{BufMut, BytesMut};
+use serde::{de, ser};
+use tokio_util::codec::{Decoder, Encoder};
+
+/// Maximum size of a single value, that `BencodeCodec::new` accepts.
+pub const DEFAULT_MAX_INPUT_SIZE: usize = 16 * 1024 * 1024;
+
+/// Maximum number of bytes reserved in the buffer at once. The length of a
+/// byte string is claimed by the peer, therefore it is not reserved as a
+/// whole.
+const RESERVE_CHUNK: usize = 64 * 1024;
+
+/// A codec, that frames a stream of bytes into consecutive Bencode values.
+///
+/// Bencode values are self-delimiting, therefore no length prefix is used.
+/// The decoder scans the incoming bytes incrementally & deserializes a value
+/// of the type `T` once it is complete. The encoder accepts any serializable
+/// type & writes `bool`s & floats the same way, as the decoder reads them.
+pub struct BencodeCodec<T = Value> {
+    options: DeserializerOptions,
+    serializer: SerializerOptions,
+    scanner: Scanner,
+    output: PhantomData<fn() -> T>,
+}
+
+impl<T> BencodeCodec<T> {
+    /// Creates a codec with the default options, except for the maximum size
+    /// of a single value, which is `DEFAULT_MAX_INPUT_SIZE`.
+    pub fn new() -> Self {
+        Self::with_options(DeserializerOptions {
+            max_input_size: DEFAULT_MAX_INPUT_SIZE,
+            ..DeserializerOptions::default()
+        })
+    }
+
+    /// Creates a codec with the given options.
+    ///
+    /// The maximum size of the input limits the size of a single value, so
+    /// that a peer can not make the decoder buffer an unbounded amount of data.
+    /// The encodings of `bool`s & floats are used by the encoder as well.
+    pub fn with_options(options: DeserializerOptions) -> Self {
+        Self {
+            options,
+            serializer: SerializerOptions {
+                bools: options.bools,
+                floats: options.floats,
+                ..SerializerOptions::default()
+            },
+            scanner: Scanner::new(options),
+            output: PhantomData,
+        }
+    }
+}
+
+impl<T> Default for BencodeCodec<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Clone for BencodeCodec<T> {
+    fn clone(&self) -> Self {
+        Self {
+            options: self.options,
+            serializer: self.serializer,
+            scanner: self.scanner.clone(),
+            output: PhantomData,
+        }
+    }
+}
+
+impl<T> fmt::Debug for BencodeCodec<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BencodeCodec")
+            .field("options", &self.options)
+            .finish()
+    }
+}
+
+impl<T> Decoder for BencodeCodec<T>
+where
+    T: de::DeserializeOwned,
+{
+    type Item = T;
+    type Error = Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<T>, Error> {
+        if src.is_empty() {
+            return Ok(None);
+        }
+
+        let length = match self.scanner.scan(src) {
+            Ok(Status::Complete(length)) => length,
+            Ok(Status::NeedMore(needed)) => {
+                src.reserve(needed.min(RESERVE_CHUNK));
+                return Ok(None);
+            }
+            Err(e) => {
+                // The stream can not be resynchronized after an error.
+                self.scanner = Scanner::new(self.options);
+                return Err(e);
+            }
+        };
+
+        self.scanner = Scanner::new(self.options);
+        let frame = src.split_to(length);
+
+        from_slice_with_options(&frame, self.options).map(Some)
+    }
+}
+
+impl<T, I> Encoder<I> for BencodeCodec<T>
+where
+    I: ser::Serialize,
+{
+    type Error = Error;
+
+    fn encode(&mut self, item: I, dst: &mut BytesMut) -> Result<(), Error> {
+        let length = dst.len();
+
+        // A partially serialized value would corrupt the stream.
+        to_writer_with_options(dst.writer(), &item, self.serializer)
+            .inspect_err(|_| dst.truncate(length))
+    }
+}
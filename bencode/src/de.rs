@@ -1,25 +1,236 @@
 //! Bencode deserialization.
 
-use std::str::{self, FromStr};
+use alloc::{boxed::Box, format, string::String, vec::Vec};
+use core::{
+    any,
+    marker::PhantomData,
+    mem,
+    str::{self, FromStr},
+};
+#[cfg(feature = "std")]
+use std::io::{self, Read as _};
 
+#[cfg(feature = "std")]
+use crate::push::{Scanner, Status};
 use crate::{
-    error::{Error, Result},
+    error::{Error, ErrorKind, Result},
+    raw,
+    ser::{self, BoolEncoding, FloatEncoding},
     token,
 };
 
 use lexical::FromLexical;
 use nom::bytes::complete::{is_a, tag, take};
-use num_traits::{Float, Signed, Unsigned};
-use serde::de;
+use num_traits::{float::FloatCore, Signed, Unsigned};
+use serde::de::{self, IntoDeserializer};
 
 /// A structure that deserializes Bencode into Rust values.
+///
+/// The deserializer keeps track of its position in the input & of the path
+/// to the value being deserialized, both of which are attached to errors.
 pub struct Deserializer<'a> {
+    input: &'a [u8],
     data: &'a [u8],
+    options: DeserializerOptions,
+    depth: usize,
+    items: usize,
+    path: Vec<Segment<'a>>,
+}
+
+/// Options of the `Deserializer`, that limit the resources used by
+/// deserialization of untrusted input & select the encodings of the types
+/// Bencode has no representation of.
+///
+/// The default options don't limit anything but the nesting depth, which
+/// protects the stack from overflowing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeserializerOptions {
+    /// Rejects any non-canonical encoding of the input, see
+    /// `Deserializer::strict`.
+    pub strict: bool,
+
+    /// Maximum nesting depth of lists & dictionaries.
+    pub max_depth: usize,
+
+    /// Maximum length of a single byte string.
+    pub max_string_length: usize,
+
+    /// Maximum total number of list elements & dictionary entries in the
+    /// input.
+    pub max_items: usize,
+
+    /// Maximum size of the whole input in bytes.
+    pub max_input_size: usize,
+
+    /// Encoding of `bool`s. Unless in the strict mode, both the string & the
+    /// integer encodings are accepted.
+    pub bools: BoolEncoding,
+
+    /// Encoding of `f32` & `f64`.
+    pub floats: FloatEncoding,
+}
+
+impl Default for DeserializerOptions {
+    fn default() -> Self {
+        Self {
+            strict: false,
+            max_depth: 128,
+            max_string_length: usize::MAX,
+            max_items: usize::MAX,
+            max_input_size: usize::MAX,
+            bools: BoolEncoding::default(),
+            floats: FloatEncoding::default(),
+        }
+    }
+}
+
+/// A segment of the path to the value being deserialized.
+enum Segment<'a> {
+    Key(&'a [u8]),
+    Index(usize),
 }
 
 impl<'a> Deserializer<'a> {
     pub fn new(data: &'a [u8]) -> Self {
-        Self { data }
+        Self::with_options(data, DeserializerOptions::default())
+    }
+
+    /// Creates a deserializer with the given options.
+    ///
+    /// Note that the size of the input is checked by the `from_*_with_options`
+    /// functions only, since the input is given up front.
+    pub fn with_options(data: &'a [u8], options: DeserializerOptions) -> Self {
+        Self {
+            input: data,
+            data,
+            options,
+            depth: 0,
+            items: 0,
+            path: Vec::new(),
+        }
+    }
+
+    /// Creates a deserializer, that rejects any non-canonical encoding of
+    /// the input.
+    ///
+    /// This means integers with leading zeros or a negative zero, string
+    /// lengths with leading zeros, and dictionaries with unsorted or
+    /// duplicate keys.
+    pub fn strict(data: &'a [u8]) -> Self {
+        Self::with_options(
+            data,
+            DeserializerOptions {
+                strict: true,
+                ..DeserializerOptions::default()
+            },
+        )
+    }
+
+    /// Returns the current byte offset into the input.
+    pub fn position(&self) -> usize {
+        self.input.len() - self.data.len()
+    }
+
+    /// Returns the remaining, not yet deserialized, part of the input.
+    pub fn remaining(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// Checks, that the whole input has been deserialized.
+    ///
+    /// This should be called after deserializing a value, unless trailing
+    /// data is expected.
+    pub fn end(&self) -> Result<()> {
+        if self.data.is_empty() {
+            Ok(())
+        } else {
+            self.locate(Err(ErrorKind::TrailingCharacters.into()))
+        }
+    }
+
+    /// Turns the deserializer into an iterator over consecutive values of
+    /// the type `T`.
+    pub fn into_stream<T>(self) -> StreamDeserializer<'a, T>
+    where
+        T: de::Deserialize<'a>,
+    {
+        StreamDeserializer {
+            de: self,
+            failed: false,
+            output: PhantomData,
+        }
+    }
+
+    /// Enters a nested list or dictionary.
+    fn enter(&mut self) -> Result<()> {
+        if self.depth >= self.options.max_depth {
+            return Err(ErrorKind::DepthLimitExceeded.into());
+        }
+
+        self.depth += 1;
+
+        Ok(())
+    }
+
+    fn leave(&mut self) {
+        self.depth -= 1;
+    }
+
+    /// Counts a list element or a dictionary entry.
+    fn count_item(&mut self) -> Result<()> {
+        if self.items >= self.options.max_items {
+            return Err(ErrorKind::TooManyItems.into());
+        }
+
+        self.items += 1;
+
+        Ok(())
+    }
+
+    /// Deserializes a nested value at the given segment of the path.
+    fn nested<T, F>(&mut self, segment: Segment<'a>, f: F) -> Result<T>
+    where
+        F: FnOnce(&mut Self) -> Result<T>,
+    {
+        self.path.push(segment);
+        let value = f(self);
+        let value = self.locate(value);
+        self.path.pop();
+
+        value
+    }
+
+    /// Formats the path to the value being deserialized, such as
+    /// `info.files[3].length`.
+    fn path(&self) -> Option<Box<str>> {
+        if self.path.is_empty() {
+            return None;
+        }
+
+        let mut path = String::new();
+
+        for segment in &self.path {
+            match segment {
+                Segment::Key(key) => {
+                    if !path.is_empty() {
+                        path.push('.');
+                    }
+
+                    path.push_str(&String::from_utf8_lossy(key));
+                }
+                Segment::Index(index) => {
+                    path.push_str(&format!("[{}]", index));
+                }
+            }
+        }
+
+        Some(path.into_boxed_str())
+    }
+
+    /// Attaches the current position & path to the error of the result,
+    /// unless it has been already attached by a nested value.
+    pub(crate) fn locate<T>(&self, result: Result<T>) -> Result<T> {
+        result.map_err(|e| e.locate(self.position(), || self.path()))
     }
 }
 
@@ -35,13 +246,74 @@ where
     T: de::Deserialize<'a>,
 {
     let mut de = Deserializer::new(data);
-    let value = de::Deserialize::deserialize(&mut de)?;
+    let value = de::Deserialize::deserialize(&mut de);
+    let value = de.locate(value)?;
+    de.end()?;
 
-    if de.data.len() == 0 {
-        Ok(value)
-    } else {
-        Err(Error::TrailingCharacters)
+    Ok(value)
+}
+
+/// Deserializes a byte slice containing Bencode format, rejecting any
+/// non-canonical encoding of the input.
+///
+/// The type of the data to be deserialized into is specified using
+/// a generic type `T`.
+///
+/// This function will also check, if any trailing characters are
+/// present at the end of the deserialization, triggering an error.
+pub fn from_slice_strict<'a, T>(data: &'a [u8]) -> Result<T>
+where
+    T: de::Deserialize<'a>,
+{
+    let mut de = Deserializer::strict(data);
+    let value = de::Deserialize::deserialize(&mut de);
+    let value = de.locate(value)?;
+    de.end()?;
+
+    Ok(value)
+}
+
+/// Deserializes a byte slice containing Bencode format, using the given
+/// options.
+///
+/// The type of the data to be deserialized into is specified using
+/// a generic type `T`.
+///
+/// This function will also check, if any trailing characters are
+/// present at the end of the deserialization, triggering an error.
+pub fn from_slice_with_options<'a, T>(data: &'a [u8], options: DeserializerOptions) -> Result<T>
+where
+    T: de::Deserialize<'a>,
+{
+    if data.len() > options.max_input_size {
+        return Err(ErrorKind::InputTooLarge.into());
     }
+
+    let mut de = Deserializer::with_options(data, options);
+    let value = de::Deserialize::deserialize(&mut de);
+    let value = de.locate(value)?;
+    de.end()?;
+
+    Ok(value)
+}
+
+/// Deserializes a value at the start of a byte slice containing Bencode
+/// format & returns it together with the rest of the input.
+///
+/// The type of the data to be deserialized into is specified using
+/// a generic type `T`.
+///
+/// This is useful for messages, where a Bencode value is followed by raw
+/// bytes, such as the `ut_metadata` extension messages.
+pub fn from_slice_prefix<'a, T>(data: &'a [u8]) -> Result<(T, &'a [u8])>
+where
+    T: de::Deserialize<'a>,
+{
+    let mut de = Deserializer::new(data);
+    let value = de::Deserialize::deserialize(&mut de);
+    let value = de.locate(value)?;
+
+    Ok((value, de.remaining()))
 }
 
 /// Deserializes a string slice containing Bencode format.
@@ -56,37 +328,95 @@ where
     T: de::Deserialize<'a>,
 {
     let mut de = Deserializer::new(data.as_bytes());
-    let value = de::Deserialize::deserialize(&mut de)?;
+    let value = de::Deserialize::deserialize(&mut de);
+    let value = de.locate(value)?;
+    de.end()?;
 
-    if de.data.len() == 0 {
-        Ok(value)
-    } else {
-        Err(Error::TrailingCharacters)
+    Ok(value)
+}
+
+/// Deserializes a single value from an IO stream containing Bencode format.
+///
+/// The type of the data to be deserialized into is specified using
+/// a generic type `T`.
+///
+/// The stream is read incrementally, until the first value is complete. No
+/// byte after the value is read, therefore the stream doesn't need to end &
+/// further values can be read from it afterwards. Since the deserialized
+/// values are allowed to borrow from the input, the value is buffered before
+/// it is deserialized.
+///
+/// The stream is read in small parts, so an unbuffered stream (e.g. a socket)
+/// should be wrapped in a `BufReader`.
+#[cfg(feature = "std")]
+pub fn from_reader<R, T>(reader: R) -> Result<T>
+where
+    R: io::Read,
+    T: de::DeserializeOwned,
+{
+    from_reader_with_options(reader, DeserializerOptions::default())
+}
+
+/// Deserializes a single value from an IO stream containing Bencode format,
+/// using the given options.
+///
+/// The type of the data to be deserialized into is specified using
+/// a generic type `T`.
+///
+/// The stream is read the same way as by `from_reader`. The depth, string
+/// length & input size limits are enforced while the stream is read, so no
+/// more than the maximum size of the input is ever buffered.
+#[cfg(feature = "std")]
+pub fn from_reader_with_options<R, T>(mut reader: R, options: DeserializerOptions) -> Result<T>
+where
+    R: io::Read,
+    T: de::DeserializeOwned,
+{
+    let mut data = Vec::new();
+    let mut scanner = Scanner::new(options);
+
+    // The scanner never asks for more bytes, than the value has left. The
+    // length is claimed by the input, therefore the bytes are read as they
+    // arrive, instead of being allocated up front.
+    while let Status::NeedMore(needed) = scanner.scan(&data)? {
+        let start = data.len();
+
+        if start.checked_add(needed).is_none() {
+            return Err(Error::from(ErrorKind::InputTooLarge).locate(start, || None));
+        }
+
+        let read = reader.by_ref().take(needed as u64).read_to_end(&mut data)?;
+
+        if read < needed {
+            return Err(Error::from(ErrorKind::EOF).locate(data.len(), || None));
+        }
     }
+
+    from_slice_with_options(&data, options)
 }
 
 //////////////////////////////////////////////////////
 
 #[inline]
-fn consume_integer_start(x: &[u8]) -> Result<&[u8]> {
+pub(crate) fn consume_integer_start(x: &[u8]) -> Result<&[u8]> {
     tag::<&[u8], &[u8], ()>(&[token::INTEGER_START])(x)
         .map(|(rest, _)| rest)
-        .map_err(|_| Error::ExpectedInteger)
+        .map_err(|_| ErrorKind::ExpectedInteger.into())
 }
 
 #[inline]
-fn consume_signed_number<T>(x: &[u8]) -> Result<(&[u8], T)>
+pub(crate) fn consume_signed_number<T>(x: &[u8]) -> Result<(&[u8], T)>
 where
     T: Signed + FromLexical,
 {
     let (rest, value) = is_a::<&[u8], &[u8], ()>(token::SIGNED_NUMBER_CHARSET)(x)
-        .map_err(|_| Error::ExpectedSignedNumber)?;
+        .map_err(|_| ErrorKind::ExpectedSignedNumber)?;
 
     let integer = lexical::parse::<T, _>(value).map_err(|e| {
         if e.is_overflow() {
-            Error::IntegerOverflow
+            ErrorKind::IntegerOverflow
         } else {
-            Error::ExpectedSignedNumber
+            ErrorKind::ExpectedSignedNumber
         }
     })?;
 
@@ -94,64 +424,129 @@ where
 }
 
 #[inline]
-fn consume_unsigned_number<T>(x: &[u8]) -> Result<(&[u8], T)>
+pub(crate) fn consume_unsigned_number<T>(x: &[u8]) -> Result<(&[u8], T)>
 where
     T: Unsigned + FromLexical,
 {
     let (rest, value) = is_a::<&[u8], &[u8], ()>(token::UNSIGNED_NUMBER_CHARSET)(x)
-        .map_err(|_| Error::ExpectedUnsignedNumber)?;
+        .map_err(|_| ErrorKind::ExpectedUnsignedNumber)?;
 
     let integer = lexical::parse::<T, _>(value).map_err(|e| {
         if e.is_overflow() {
-            Error::IntegerOverflow
+            ErrorKind::IntegerOverflow
         } else {
-            Error::ExpectedUnsignedNumber
+            ErrorKind::ExpectedUnsignedNumber
         }
     })?;
 
     Ok((rest, integer))
 }
 
+/// Checks, that the number is an optional minus sign followed by at least
+/// one digit, the same way as `consume_signed_number` does, but without a
+/// limit on the number of digits.
+#[inline]
+pub(crate) fn check_signed_number(x: &[u8]) -> Result<()> {
+    let digits = match x {
+        [b'-', digits @ ..] => digits,
+        digits => digits,
+    };
+
+    if !digits.is_empty() && digits.iter().all(u8::is_ascii_digit) {
+        Ok(())
+    } else {
+        Err(ErrorKind::ExpectedSignedNumber.into())
+    }
+}
+
+/// Checks, that the integer is in its canonical form, i.e. an optional minus
+/// sign followed by digits without any leading zeros, except for zero itself
+/// (which can not be negative).
+#[inline]
+fn check_canonical_integer(x: &[u8]) -> Result<()> {
+    let digits = match is_a::<&[u8], &[u8], ()>(token::SIGNED_NUMBER_CHARSET)(x) {
+        Ok((_, digits)) => digits,
+        // Missing number is reported during parsing.
+        Err(_) => return Ok(()),
+    };
+
+    let canonical = match digits {
+        [b'0'] => true,
+        [b'-', b'1'..=b'9', rest @ ..] | [b'1'..=b'9', rest @ ..] => {
+            rest.iter().all(u8::is_ascii_digit)
+        }
+        _ => false,
+    };
+
+    if canonical {
+        Ok(())
+    } else {
+        Err(ErrorKind::NonCanonicalInteger.into())
+    }
+}
+
+/// Checks, that the length of a byte string has no leading zeros.
+#[inline]
+fn check_canonical_length(x: &[u8]) -> Result<()> {
+    match is_a::<&[u8], &[u8], ()>(token::UNSIGNED_NUMBER_CHARSET)(x) {
+        Ok((_, [b'0', _, ..])) => Err(ErrorKind::NonCanonicalStringLength.into()),
+        _ => Ok(()),
+    }
+}
+
 #[inline]
-fn consume_bytes_delimiter(x: &[u8]) -> Result<&[u8]> {
+pub(crate) fn consume_bytes_delimiter(x: &[u8]) -> Result<&[u8]> {
     tag::<&[u8], &[u8], ()>(&[token::BYTES_DELIMITER])(x)
         .map(|(rest, _)| rest)
-        .map_err(|_| Error::ExpectedStringIntegerLength)
+        .map_err(|_| ErrorKind::ExpectedStringIntegerLength.into())
 }
 
 #[inline]
-fn consume_bytes(x: &[u8], count: usize) -> Result<(&[u8], &[u8])> {
-    take::<usize, &[u8], ()>(count)(x).map_err(|_| Error::EOF)
+pub(crate) fn consume_bytes(x: &[u8], count: usize) -> Result<(&[u8], &[u8])> {
+    take::<usize, &[u8], ()>(count)(x).map_err(|_| ErrorKind::EOF.into())
 }
 
 #[inline]
-fn consume_end(x: &[u8], e: Error) -> Result<&[u8]> {
+pub(crate) fn consume_end(x: &[u8], e: ErrorKind) -> Result<&[u8]> {
     tag::<&[u8], &[u8], ()>(&[token::END])(x)
         .map(|(rest, _)| rest)
-        .map_err(|_| e)
+        .map_err(|_| e.into())
 }
 
 //////////////////////////////////////////////////////
 
 impl<'a> Deserializer<'a> {
-    fn peek_byte(&mut self, index: usize) -> Result<u8> {
-        self.data.get(index).ok_or(Error::EOF).map(|v| v.to_owned())
+    fn peek_byte(&self, index: usize) -> Result<u8> {
+        self.data
+            .get(index)
+            .copied()
+            .ok_or_else(|| ErrorKind::EOF.into())
     }
 
+    #[allow(clippy::get_first)]
     fn next_byte(&mut self) -> Result<u8> {
-        let byte = self.data.get(0).ok_or(Error::EOF).map(|b| b.to_owned())?;
+        let byte = self.data.get(0).ok_or(ErrorKind::EOF).copied()?;
         self.data = &self.data[1..];
 
         Ok(byte)
     }
 
+    fn check_canonical_integer(&self, data: &[u8]) -> Result<()> {
+        if self.options.strict {
+            check_canonical_integer(data)
+        } else {
+            Ok(())
+        }
+    }
+
     fn parse_signed<T>(&mut self) -> Result<T>
     where
         T: Signed + FromLexical,
     {
         let data = consume_integer_start(self.data)?;
+        self.check_canonical_integer(data)?;
         let (data, number) = consume_signed_number::<T>(data)?;
-        self.data = consume_end(data, Error::ExpectedIntegerEnd)?;
+        self.data = consume_end(data, ErrorKind::ExpectedIntegerEnd)?;
 
         Ok(number)
     }
@@ -161,14 +556,34 @@ impl<'a> Deserializer<'a> {
         T: Unsigned + FromLexical,
     {
         let data = consume_integer_start(self.data)?;
+        self.check_canonical_integer(data)?;
         let (data, number) = consume_unsigned_number::<T>(data)?;
-        self.data = consume_end(data, Error::ExpectedIntegerEnd)?;
+        self.data = consume_end(data, ErrorKind::ExpectedIntegerEnd)?;
+
+        Ok(number)
+    }
+
+    fn parse_raw_integer(&mut self) -> Result<&'a [u8]> {
+        let data = consume_integer_start(self.data)?;
+        self.check_canonical_integer(data)?;
+        let (data, number) = is_a::<&[u8], &[u8], ()>(token::SIGNED_NUMBER_CHARSET)(data)
+            .map_err(|_| ErrorKind::ExpectedSignedNumber)?;
+        check_signed_number(number)?;
+        self.data = consume_end(data, ErrorKind::ExpectedIntegerEnd)?;
 
         Ok(number)
     }
 
     fn parse_bytes(&mut self) -> Result<&'a [u8]> {
+        if self.options.strict {
+            check_canonical_length(self.data)?;
+        }
+
         let (data, count) = consume_unsigned_number::<usize>(self.data)?;
+
+        if count > self.options.max_string_length {
+            return Err(ErrorKind::StringTooLong.into());
+        }
         let data = consume_bytes_delimiter(data)?;
         let (data, bytes) = consume_bytes(data, count)?;
         self.data = data;
@@ -176,30 +591,152 @@ impl<'a> Deserializer<'a> {
         Ok(bytes)
     }
 
+    #[allow(clippy::needless_borrow)]
     fn parse_string(&mut self) -> Result<&'a str> {
         let bytes = self.parse_bytes()?;
-        let string = str::from_utf8(&bytes).map_err(|_| Error::InvalidUTF8)?;
+        let string = str::from_utf8(&bytes).map_err(|_| ErrorKind::InvalidUTF8)?;
 
         Ok(string)
     }
 
     fn parse_float<T>(&mut self) -> Result<T>
     where
-        T: Float + FromStr,
+        T: FloatCore + FromStr,
     {
-        let string = self.parse_string()?;
-        let float = string.parse::<T>().map_err(|_| Error::ExpectedFloat)?;
+        match self.options.floats {
+            FloatEncoding::String => {
+                let string = self.parse_string()?;
+                let float = string.parse::<T>().map_err(|_| ErrorKind::ExpectedFloat)?;
+
+                Ok(float)
+            }
+            FloatEncoding::FixedPoint(decimals) => {
+                let scaled = self.parse_signed::<i64>()?;
+                let float = scaled as f64 / ser::fixed_point_scale(decimals)?;
 
-        Ok(float)
+                num_traits::cast(float).ok_or_else(|| ErrorKind::ExpectedFloat.into())
+            }
+            FloatEncoding::Error => Err(ErrorKind::UnsupportedFloat.into()),
+        }
     }
 
     fn parse_bool(&mut self) -> Result<bool> {
-        match self.parse_string()? {
-            "true" => Ok(true),
-            "false" => Ok(false),
-            _ => Err(Error::UnknownType),
+        // Both of the encodings are accepted, unless in the strict mode.
+        let (integers, strings) = match self.options.bools {
+            BoolEncoding::String => (!self.options.strict, true),
+            BoolEncoding::Integer => (true, !self.options.strict),
+            BoolEncoding::Error => return Err(ErrorKind::UnsupportedBool.into()),
+        };
+
+        let integer = if integers && strings {
+            self.peek_byte(0)? == token::INTEGER_START
+        } else {
+            integers
+        };
+
+        if integer {
+            match self.parse_unsigned::<u8>()? {
+                1 => Ok(true),
+                0 => Ok(false),
+                _ => Err(ErrorKind::UnknownType.into()),
+            }
+        } else {
+            match self.parse_string()? {
+                "true" => Ok(true),
+                "false" => Ok(false),
+                _ => Err(ErrorKind::UnknownType.into()),
+            }
+        }
+    }
+
+    /// Consumes the next value without decoding it, validating only its
+    /// structure.
+    pub(crate) fn skip_value(&mut self) -> Result<()> {
+        self.walk_value(&mut |_| ())
+    }
+
+    /// Consumes the next value the same way as `skip_value`, reporting the
+    /// boundaries of the value & of all the nested values & keys to `f`.
+    pub(crate) fn walk_value<F>(&mut self, f: &mut F) -> Result<()>
+    where
+        F: FnMut(Walk),
+    {
+        f(Walk::Start(self.position()));
+
+        match self.peek_byte(0)? {
+            b'0'..=b'9' => {
+                self.parse_bytes()?;
+            }
+            token::INTEGER_START => {
+                self.parse_raw_integer()?;
+            }
+            token::LIST_START => {
+                self.next_byte()?;
+                self.enter()?;
+
+                while self.peek_byte(0)? != token::END {
+                    self.count_item()?;
+                    self.walk_value(f)?;
+                }
+
+                self.leave();
+                self.next_byte()?;
+            }
+            token::MAP_START => {
+                self.next_byte()?;
+                self.enter()?;
+
+                let mut keys = KeyOrder::default();
+
+                while self.peek_byte(0)? != token::END {
+                    self.count_item()?;
+                    let start = self.data;
+                    f(Walk::Start(self.position()));
+                    let key = self
+                        .parse_bytes()
+                        .map_err(|_| ErrorKind::ExpectedDictionaryKeyString)?;
+
+                    if let Err(e) = self.check_key_order(&mut keys, key) {
+                        // Report the error at the start of the key.
+                        self.data = start;
+                        return Err(e);
+                    }
+
+                    f(Walk::End(self.position()));
+                    self.walk_value(f)?;
+                }
+
+                self.leave();
+                self.next_byte()?;
+            }
+            _ => return Err(ErrorKind::UnknownType.into()),
+        }
+
+        f(Walk::End(self.position()));
+
+        Ok(())
+    }
+
+    /// Checks, that the dictionary key comes strictly after the previous one.
+    fn check_key_order(&self, keys: &mut KeyOrder<'a>, key: &'a [u8]) -> Result<()> {
+        if !self.options.strict {
+            return Ok(());
+        }
+
+        match keys.last.replace(key) {
+            Some(last) if last == key => Err(ErrorKind::DuplicateKey.into()),
+            Some(last) if last > key => Err(ErrorKind::UnsortedKeys.into()),
+            _ => Ok(()),
         }
     }
+
+    /// Consumes the next value & returns the bytes it spans in the input.
+    fn parse_raw(&mut self) -> Result<&'a [u8]> {
+        let start = self.data;
+        self.skip_value()?;
+
+        Ok(&start[..start.len() - self.data.len()])
+    }
 }
 
 //////////////////////////////////////////////////////
@@ -226,25 +763,71 @@ macro_rules! fn_deserialize_signed {
     };
 }
 
+/// Visits the integer as `u64` or `i64`, unless it is out of their ranges.
+pub(crate) fn visit_integer<'de, V>(integer: i128, visitor: V) -> Result<V::Value>
+where
+    V: de::Visitor<'de>,
+{
+    if let Ok(value) = u64::try_from(integer) {
+        visitor.visit_u64(value)
+    } else if let Ok(value) = i64::try_from(integer) {
+        visitor.visit_i64(value)
+    } else {
+        visitor.visit_i128(integer)
+    }
+}
+
+/// Checks, whether the visitor is the one `serde` buffers values with. It is
+/// not public, therefore it is recognized by its name.
+fn is_buffered<'de, V>() -> bool
+where
+    V: de::Visitor<'de>,
+{
+    let name = any::type_name::<V>();
+    let path = name.split('<').next().unwrap_or(name);
+
+    path.ends_with("::ContentVisitor")
+}
+
+#[allow(clippy::needless_lifetimes)]
 impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
     type Error = Error;
 
+    /// Values buffered by `serde` (e.g. for a struct with a flattened field,
+    /// or an untagged enum) lose their encoding, therefore these have to be
+    /// canonical even outside of the strict mode. Otherwise an `Extra` field
+    /// would silently write back different bytes & change the info-hash.
     fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
     where
         V: de::Visitor<'de>,
     {
+        let canonical = !self.options.strict && is_buffered::<V>();
+
         match self.peek_byte(0)? {
-            b'0'..=b'9' => self.deserialize_str(visitor),
+            b'0'..=b'9' => {
+                if canonical {
+                    check_canonical_length(self.data)?;
+                }
+
+                // Byte strings are visited as strings, when they are valid
+                // UTF-8, otherwise they are visited as raw bytes.
+                let bytes = self.parse_bytes()?;
+
+                match str::from_utf8(bytes) {
+                    Ok(string) => visitor.visit_borrowed_str(string),
+                    Err(_) => visitor.visit_borrowed_bytes(bytes),
+                }
+            }
             token::INTEGER_START => {
-                if let b'-' = self.peek_byte(1)? {
-                    self.deserialize_i64(visitor)
-                } else {
-                    self.deserialize_u64(visitor)
+                if canonical {
+                    check_canonical_integer(consume_integer_start(self.data)?)?;
                 }
+
+                visit_integer(self.parse_signed::<i128>()?, visitor)
             }
             token::LIST_START => self.deserialize_seq(visitor),
             token::MAP_START => self.deserialize_map(visitor),
-            _ => Err(Error::UnknownType),
+            _ => Err(ErrorKind::UnknownType.into()),
         }
     }
 
@@ -259,17 +842,13 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
     fn_deserialize_unsigned!(deserialize_u16, visit_u16, u16);
     fn_deserialize_unsigned!(deserialize_u32, visit_u32, u32);
     fn_deserialize_unsigned!(deserialize_u64, visit_u64, u64);
-    serde::serde_if_integer128! {
-        fn_deserialize_unsigned!(deserialize_u128, visit_u128, u128);
-    }
+    fn_deserialize_unsigned!(deserialize_u128, visit_u128, u128);
 
     fn_deserialize_signed!(deserialize_i8, visit_i8, i8);
     fn_deserialize_signed!(deserialize_i16, visit_i16, i16);
     fn_deserialize_signed!(deserialize_i32, visit_i32, i32);
     fn_deserialize_signed!(deserialize_i64, visit_i64, i64);
-    serde::serde_if_integer128! {
-        fn_deserialize_signed!(deserialize_i128, visit_i128, i128);
-    }
+    fn_deserialize_signed!(deserialize_i128, visit_i128, i128);
 
     fn deserialize_str<V>(self, visitor: V) -> Result<V::Value>
     where
@@ -327,20 +906,23 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
         self.deserialize_bytes(visitor)
     }
 
+    #[allow(clippy::needless_borrow)]
     fn deserialize_seq<V>(mut self, visitor: V) -> Result<V::Value>
     where
         V: de::Visitor<'de>,
     {
         if let token::LIST_START = self.next_byte()? {
+            self.enter()?;
             let value = visitor.visit_seq(ListDeserializer::new(&mut self))?;
+            self.leave();
 
             if let token::END = self.next_byte()? {
                 Ok(value)
             } else {
-                Err(Error::ExpectedListEnd)
+                Err(ErrorKind::ExpectedListEnd.into())
             }
         } else {
-            Err(Error::ExpectedList)
+            Err(ErrorKind::ExpectedList.into())
         }
     }
 
@@ -363,20 +945,23 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
         self.deserialize_seq(visitor)
     }
 
+    #[allow(clippy::needless_borrow)]
     fn deserialize_map<V>(mut self, visitor: V) -> Result<V::Value>
     where
         V: de::Visitor<'de>,
     {
         if let token::MAP_START = self.next_byte()? {
+            self.enter()?;
             let value = visitor.visit_map(MapDeserializer::new(&mut self))?;
+            self.leave();
 
             if let token::END = self.next_byte()? {
                 Ok(value)
             } else {
-                Err(Error::ExpectedDictionaryEnd)
+                Err(ErrorKind::ExpectedDictionaryEnd.into())
             }
         } else {
-            Err(Error::ExpectedDictionary)
+            Err(ErrorKind::ExpectedDictionary.into())
         }
     }
 
@@ -392,10 +977,66 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
         self.deserialize_map(visitor)
     }
 
+    fn deserialize_newtype_struct<V>(self, name: &'static str, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        if name == raw::TOKEN {
+            visitor.visit_borrowed_bytes(self.parse_raw()?)
+        } else if name == ser::INT_BOOL_TOKEN {
+            let bools = mem::replace(&mut self.options.bools, BoolEncoding::Integer);
+            let result = visitor.visit_newtype_struct(&mut *self);
+            self.options.bools = bools;
+
+            result
+        } else {
+            visitor.visit_newtype_struct(self)
+        }
+    }
+
+    /// Enums are deserialized from the externally tagged representation, i.e.
+    /// unit variants from a string & the other variants from a dictionary
+    /// with a single key `d<length>:<variant><value>e`.
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.peek_byte(0)? {
+            b'0'..=b'9' => visitor.visit_enum(self.parse_string()?.into_deserializer()),
+            token::MAP_START => {
+                self.next_byte()?;
+                self.enter()?;
+                let value = visitor.visit_enum(EnumDeserializer::new(self))?;
+                self.leave();
+
+                if let token::END = self.next_byte()? {
+                    Ok(value)
+                } else {
+                    Err(ErrorKind::ExpectedDictionaryEnd.into())
+                }
+            }
+            _ => Err(ErrorKind::ExpectedEnum.into()),
+        }
+    }
+
+    /// `None` is never serialized, therefore any present value is `Some`.
+    /// Missing dictionary keys of `Option` fields are handled by the derived
+    /// `Deserialize` implementations.
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_some(self)
+    }
+
     serde::forward_to_deserialize_any! {
         char
-        unit unit_struct option
-        enum newtype_struct
+        unit unit_struct
     }
 }
 
@@ -403,11 +1044,12 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
 
 struct ListDeserializer<'de, 'a> {
     de: &'a mut Deserializer<'de>,
+    index: usize,
 }
 
 impl<'de, 'a> ListDeserializer<'de, 'a> {
     fn new(de: &'a mut Deserializer<'de>) -> Self {
-        ListDeserializer { de }
+        ListDeserializer { de, index: 0 }
     }
 }
 
@@ -418,23 +1060,85 @@ impl<'de, 'a> de::SeqAccess<'de> for ListDeserializer<'de, 'a> {
     where
         T: de::DeserializeSeed<'de>,
     {
-        if let token::END = self.de.peek_byte(0)? {
-            Ok(None)
-        } else {
-            seed.deserialize(&mut *self.de).map(Some)
+        if let token::END = self.de.locate(self.de.peek_byte(0))? {
+            return Ok(None);
         }
+
+        let count = self.de.count_item();
+        self.de.locate(count)?;
+
+        let value = self
+            .de
+            .nested(Segment::Index(self.index), |de| seed.deserialize(de));
+
+        self.index += 1;
+        value.map(Some)
     }
 }
 
 //////////////////////////////////////////////////////
 
+/// A boundary of a value passed by `Deserializer::walk_value`, given by its
+/// byte offset.
+pub(crate) enum Walk {
+    Start(usize),
+    End(usize),
+}
+
+/// The last key of a dictionary, used to check the order of the keys in the
+/// strict mode.
+#[derive(Default)]
+struct KeyOrder<'a> {
+    last: Option<&'a [u8]>,
+}
+
 struct MapDeserializer<'de, 'a> {
     de: &'a mut Deserializer<'de>,
+    keys: KeyOrder<'de>,
+    key: Option<&'de [u8]>,
 }
 
 impl<'de, 'a> MapDeserializer<'de, 'a> {
     fn new(de: &'a mut Deserializer<'de>) -> Self {
-        MapDeserializer { de }
+        MapDeserializer {
+            de,
+            keys: KeyOrder::default(),
+            key: None,
+        }
+    }
+
+    fn next_key<K>(&mut self, seed: K) -> Result<Option<K::Value>>
+    where
+        K: de::DeserializeSeed<'de>,
+    {
+        match self.de.peek_byte(0)? {
+            token::END => Ok(None),
+            b'0'..=b'9' => {
+                self.de.count_item()?;
+
+                let start = self.de.data;
+                let value = seed.deserialize(MapKeyDeserializer { de: self.de })?;
+
+                // The raw bytes of the key follow the first delimiter of
+                // the consumed byte string.
+                let span = &start[..start.len() - self.de.data.len()];
+                let key = span
+                    .iter()
+                    .position(|&b| b == token::BYTES_DELIMITER)
+                    .map_or(span, |index| &span[index + 1..]);
+
+                if let Err(e) = self.de.check_key_order(&mut self.keys, key) {
+                    // Point the error at the offending key.
+                    self.de.data = start;
+                    return Err(e);
+                }
+
+                self.key = Some(key);
+
+                Ok(Some(value))
+            }
+            _ => Err(ErrorKind::ExpectedDictionaryKeyString.into()),
+        }
     }
 }
 
@@ -445,17 +1149,303 @@ impl<'de, 'a> de::MapAccess<'de> for MapDeserializer<'de, 'a> {
     where
         K: de::DeserializeSeed<'de>,
     {
-        match self.de.peek_byte(0)? {
-            token::END => Ok(None),
-            b'0'..=b'9' => seed.deserialize(&mut *self.de).map(Some),
-            _ => Err(Error::ExpectedDictionaryKeyString),
-        }
+        let key = self.next_key(seed);
+        self.de.locate(key)
     }
 
     fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value>
     where
         V: de::DeserializeSeed<'de>,
     {
-        seed.deserialize(&mut *self.de)
+        let key = self.key.take().unwrap_or_default();
+
+        self.de.nested(Segment::Key(key), |de| seed.deserialize(de))
+    }
+}
+
+//////////////////////////////////////////////////////
+
+/// A deserializer of dictionary keys.
+///
+/// Keys are byte strings, therefore besides strings they can be deserialized
+/// as raw bytes, including the types that deserialize a sequence of bytes
+/// (e.g. `Vec<u8>` or `[u8; 20]`). Integers are parsed from their string
+/// representation, the same way `Serializer` writes integer keys.
+struct MapKeyDeserializer<'de, 'a> {
+    de: &'a mut Deserializer<'de>,
+}
+
+/// Integer keys are parsed by the same rules as integer values, including
+/// the canonical form in the strict mode.
+macro_rules! fn_deserialize_integer_key {
+    ($method:ident, $visit:ident, $type:ty, $consume:ident) => {
+        fn $method<V>(self, visitor: V) -> Result<V::Value>
+        where
+            V: de::Visitor<'de>,
+        {
+            let key = self.de.parse_bytes()?;
+            self.de.check_canonical_integer(key)?;
+
+            visitor.$visit(integer_key($consume::<$type>(key))?)
+        }
+    };
+}
+
+/// Checks, that the parsed integer spans the whole key.
+pub(crate) fn integer_key<T>(result: Result<(&[u8], T)>) -> Result<T> {
+    match result {
+        Ok(([], number)) => Ok(number),
+        Err(e) if matches!(e.kind(), ErrorKind::IntegerOverflow) => Err(e),
+        _ => Err(ErrorKind::ExpectedInteger.into()),
+    }
+}
+
+impl<'de, 'a> de::Deserializer<'de> for MapKeyDeserializer<'de, 'a> {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.de.deserialize_any(visitor)
+    }
+
+    fn_deserialize_integer_key!(deserialize_u8, visit_u8, u8, consume_unsigned_number);
+    fn_deserialize_integer_key!(deserialize_u16, visit_u16, u16, consume_unsigned_number);
+    fn_deserialize_integer_key!(deserialize_u32, visit_u32, u32, consume_unsigned_number);
+    fn_deserialize_integer_key!(deserialize_u64, visit_u64, u64, consume_unsigned_number);
+    fn_deserialize_integer_key!(deserialize_u128, visit_u128, u128, consume_unsigned_number);
+
+    fn_deserialize_integer_key!(deserialize_i8, visit_i8, i8, consume_signed_number);
+    fn_deserialize_integer_key!(deserialize_i16, visit_i16, i16, consume_signed_number);
+    fn_deserialize_integer_key!(deserialize_i32, visit_i32, i32, consume_signed_number);
+    fn_deserialize_integer_key!(deserialize_i64, visit_i64, i64, consume_signed_number);
+    fn_deserialize_integer_key!(deserialize_i128, visit_i128, i128, consume_signed_number);
+
+    fn deserialize_str<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.de.deserialize_str(visitor)
+    }
+
+    fn deserialize_string<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.de.deserialize_str(visitor)
+    }
+
+    /// Keys, that are not valid UTF-8, are visited as bytes, so that they
+    /// can be ignored as unknown fields or kept by a flattened field.
+    fn deserialize_identifier<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.de.deserialize_any(visitor)
+    }
+
+    fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.de.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.de.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        let bytes = self.de.parse_bytes()?;
+
+        let mut seq = de::value::SeqDeserializer::<_, Error>::new(bytes.iter().copied());
+        let value = visitor.visit_seq(&mut seq)?;
+        seq.end()?;
+
+        Ok(value)
+    }
+
+    fn deserialize_tuple<V>(self, _len: usize, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.de.deserialize_enum(name, variants, visitor)
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool f32 f64 char unit unit_struct map struct ignored_any
+    }
+}
+
+//////////////////////////////////////////////////////
+
+struct EnumDeserializer<'de, 'a> {
+    de: &'a mut Deserializer<'de>,
+    variant: &'de [u8],
+}
+
+impl<'de, 'a> EnumDeserializer<'de, 'a> {
+    fn new(de: &'a mut Deserializer<'de>) -> Self {
+        EnumDeserializer { de, variant: &[] }
+    }
+}
+
+impl<'de, 'a> de::EnumAccess<'de> for EnumDeserializer<'de, 'a> {
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<V>(mut self, seed: V) -> Result<(V::Value, Self)>
+    where
+        V: de::DeserializeSeed<'de>,
+    {
+        if !self.de.peek_byte(0)?.is_ascii_digit() {
+            return Err(ErrorKind::ExpectedDictionaryKeyString.into());
+        }
+
+        self.de.count_item()?;
+
+        // Peek the raw bytes of the variant first, so that it can be used as
+        // the path of the variant's value.
+        let data = self.de.data;
+        self.variant = self.de.parse_bytes()?;
+        self.de.data = data;
+
+        let value = seed.deserialize(&mut *self.de)?;
+
+        Ok((value, self))
+    }
+}
+
+impl<'de, 'a> de::VariantAccess<'de> for EnumDeserializer<'de, 'a> {
+    type Error = Error;
+
+    /// Unit variants are represented as a string only, never as a dictionary.
+    fn unit_variant(self) -> Result<()> {
+        Err(de::Error::invalid_type(
+            de::Unexpected::NewtypeVariant,
+            &"unit variant",
+        ))
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        self.de
+            .nested(Segment::Key(self.variant), |de| seed.deserialize(de))
+    }
+
+    fn tuple_variant<V>(self, _len: usize, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.de.nested(Segment::Key(self.variant), |de| {
+            de::Deserializer::deserialize_seq(de, visitor)
+        })
+    }
+
+    fn struct_variant<V>(self, _fields: &'static [&'static str], visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.de.nested(Segment::Key(self.variant), |de| {
+            de::Deserializer::deserialize_map(de, visitor)
+        })
+    }
+}
+
+//////////////////////////////////////////////////////
+
+/// An iterator, that deserializes consecutive Bencode values of the type `T`
+/// from a byte slice.
+///
+/// The iteration stops at the end of the input, or after the first error.
+pub struct StreamDeserializer<'a, T> {
+    de: Deserializer<'a>,
+    failed: bool,
+    output: PhantomData<T>,
+}
+
+impl<'a, T> StreamDeserializer<'a, T>
+where
+    T: de::Deserialize<'a>,
+{
+    /// Creates an iterator over the values of a byte slice.
+    pub fn new(data: &'a [u8]) -> Self {
+        Deserializer::new(data).into_stream()
+    }
+
+    /// Returns the byte offset of the next value in the input.
+    pub fn byte_offset(&self) -> usize {
+        self.de.position()
+    }
+
+    /// Returns the remaining, not yet deserialized, part of the input.
+    pub fn remaining(&self) -> &'a [u8] {
+        self.de.remaining()
+    }
+}
+
+impl<'a, T> Iterator for StreamDeserializer<'a, T>
+where
+    T: de::Deserialize<'a>,
+{
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed || self.de.data.is_empty() {
+            return None;
+        }
+
+        let value = T::deserialize(&mut self.de);
+        let value = self.de.locate(value);
+        self.failed = value.is_err();
+
+        Some(value)
     }
 }
@@ -0,0 +1,127 @@
+//! Support of the code generated by `#[derive(Bencode)]`.
+
+use alloc::{borrow::Cow, string::String, vec::Vec};
+use core::fmt;
+
+use crate::ser::INT_BOOL_TOKEN;
+
+use serde::{de, ser};
+
+/// A value of a `#[bencode(int_bool)]` field, that is (de)serialized with
+/// `BoolEncoding::Integer`, whatever the options of the (de)serializer are.
+pub struct IntBool<T>(pub T);
+
+impl ser::Serialize for IntBool<&bool> {
+    fn serialize<S>(&self, serializer: S) -> core::result::Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        serializer.serialize_newtype_struct(INT_BOOL_TOKEN, self.0)
+    }
+}
+
+impl<'de> de::Deserialize<'de> for IntBool<bool> {
+    fn deserialize<D>(deserializer: D) -> core::result::Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        struct IntBoolVisitor;
+
+        impl<'de> de::Visitor<'de> for IntBoolVisitor {
+            type Value = IntBool<bool>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("an integer 0 or 1")
+            }
+
+            fn visit_newtype_struct<D>(
+                self,
+                deserializer: D,
+            ) -> core::result::Result<Self::Value, D::Error>
+            where
+                D: de::Deserializer<'de>,
+            {
+                de::Deserialize::deserialize(deserializer).map(IntBool)
+            }
+        }
+
+        deserializer.deserialize_newtype_struct(INT_BOOL_TOKEN, IntBoolVisitor)
+    }
+}
+
+/// A key of a dictionary, that is borrowed from the input, if possible.
+pub struct FieldKey<'de>(Cow<'de, [u8]>);
+
+impl<'de> FieldKey<'de> {
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    pub fn into_owned(self) -> Vec<u8> {
+        self.0.into_owned()
+    }
+}
+
+impl<'a> From<&'a [u8]> for FieldKey<'a> {
+    fn from(key: &'a [u8]) -> Self {
+        FieldKey(Cow::Borrowed(key))
+    }
+}
+
+impl ser::Serialize for FieldKey<'_> {
+    fn serialize<S>(&self, serializer: S) -> core::result::Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        serializer.serialize_bytes(&self.0)
+    }
+}
+
+impl<'de> de::Deserialize<'de> for FieldKey<'de> {
+    fn deserialize<D>(deserializer: D) -> core::result::Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        struct FieldKeyVisitor;
+
+        impl<'de> de::Visitor<'de> for FieldKeyVisitor {
+            type Value = FieldKey<'de>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a byte string key")
+            }
+
+            fn visit_borrowed_bytes<E>(
+                self,
+                value: &'de [u8],
+            ) -> core::result::Result<Self::Value, E> {
+                Ok(FieldKey(Cow::Borrowed(value)))
+            }
+
+            fn visit_borrowed_str<E>(
+                self,
+                value: &'de str,
+            ) -> core::result::Result<Self::Value, E> {
+                Ok(FieldKey(Cow::Borrowed(value.as_bytes())))
+            }
+
+            fn visit_bytes<E>(self, value: &[u8]) -> core::result::Result<Self::Value, E> {
+                Ok(FieldKey(Cow::Owned(value.to_vec())))
+            }
+
+            fn visit_str<E>(self, value: &str) -> core::result::Result<Self::Value, E> {
+                Ok(FieldKey(Cow::Owned(value.as_bytes().to_vec())))
+            }
+
+            fn visit_byte_buf<E>(self, value: Vec<u8>) -> core::result::Result<Self::Value, E> {
+                Ok(FieldKey(Cow::Owned(value)))
+            }
+
+            fn visit_string<E>(self, value: String) -> core::result::Result<Self::Value, E> {
+                Ok(FieldKey(Cow::Owned(value.into_bytes())))
+            }
+        }
+
+        deserializer.deserialize_bytes(FieldKeyVisitor)
+    }
+}
@@ -0,0 +1,313 @@
+//! Zero-copy, indexed Bencode document.
+
+use alloc::{string::String, vec::Vec};
+use core::fmt;
+
+use crate::{
+    de::{from_slice, Deserializer, DeserializerOptions, Walk},
+    error::Result,
+    raw::RawValue,
+    token,
+};
+
+use serde::de;
+
+/// Boundaries of a single value (or a dictionary key) in the document.
+#[derive(Debug, Clone, Copy)]
+struct Span {
+    start: usize,
+    end: usize,
+    /// Index of the span after the value & all of its nested values.
+    next: usize,
+}
+
+/// A Bencode document, that borrows the input & holds the boundaries of all
+/// of its values.
+///
+/// The boundaries are recorded in a single pass over the input, without
+/// decoding any of the values. The values are navigated through `Node`s &
+/// only the ones, that are actually needed, are deserialized. This is much
+/// faster than deserializing the whole document, when only a few values of
+/// a large document are of an interest (e.g. the files of a torrent).
+///
+/// Keys of dictionaries are looked up & elements of lists are accessed by
+/// passing over the preceding values, without decoding them.
+pub struct Document<'a> {
+    data: &'a [u8],
+    spans: Vec<Span>,
+}
+
+impl<'a> Document<'a> {
+    /// Indexes a byte slice containing a single Bencode value.
+    pub fn new(data: &'a [u8]) -> Result<Self> {
+        Self::with_options(data, DeserializerOptions::default())
+    }
+
+    /// Indexes a byte slice containing a single Bencode value, using the
+    /// given options.
+    ///
+    /// The values deserialized from the document later on are deserialized
+    /// with the default options.
+    pub fn with_options(data: &'a [u8], options: DeserializerOptions) -> Result<Self> {
+        let mut spans = Vec::new();
+        let mut open = Vec::new();
+
+        let mut de = Deserializer::with_options(data, options);
+        let result = de.walk_value(&mut |walk| match walk {
+            Walk::Start(start) => {
+                open.push(spans.len());
+                spans.push(Span {
+                    start,
+                    end: start,
+                    next: 0,
+                });
+            }
+            Walk::End(end) => {
+                if let Some(index) = open.pop() {
+                    let next = spans.len();
+                    let span = &mut spans[index];
+                    span.end = end;
+                    span.next = next;
+                }
+            }
+        });
+
+        de.locate(result)?;
+        de.end()?;
+
+        Ok(Self { data, spans })
+    }
+
+    /// Returns the top-level value of the document.
+    pub fn root(&self) -> Node<'_, 'a> {
+        Node {
+            document: self,
+            index: 0,
+        }
+    }
+
+    /// Returns a value of the top-level dictionary.
+    pub fn get<K>(&self, key: K) -> Option<Node<'_, 'a>>
+    where
+        K: AsRef<[u8]>,
+    {
+        self.root().get(key)
+    }
+}
+
+impl fmt::Debug for Document<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Document")
+            .field("length", &self.data.len())
+            .field("values", &self.spans.len())
+            .finish()
+    }
+}
+
+/// A single value of a `Document`.
+#[derive(Clone, Copy)]
+pub struct Node<'d, 'a> {
+    document: &'d Document<'a>,
+    index: usize,
+}
+
+impl<'d, 'a> Node<'d, 'a> {
+    fn span(&self) -> Span {
+        self.document.spans[self.index]
+    }
+
+    fn first_byte(&self) -> u8 {
+        self.document.data[self.span().start]
+    }
+
+    /// Returns the raw bytes of the value, as they appear in the input.
+    pub fn as_raw(&self) -> &'a [u8] {
+        let span = self.span();
+
+        &self.document.data[span.start..span.end]
+    }
+
+    /// Returns the value as a `RawValue`, that borrows the input.
+    pub fn to_raw_value(&self) -> RawValue<'a> {
+        // The bytes have already been validated while indexing.
+        RawValue::borrowed(self.as_raw())
+    }
+
+    /// Deserializes the value into a type `T`.
+    pub fn decode<T>(&self) -> Result<T>
+    where
+        T: de::Deserialize<'a>,
+    {
+        from_slice(self.as_raw())
+    }
+
+    pub fn is_int(&self) -> bool {
+        self.first_byte() == token::INTEGER_START
+    }
+
+    pub fn is_bytes(&self) -> bool {
+        self.first_byte().is_ascii_digit()
+    }
+
+    pub fn is_list(&self) -> bool {
+        self.first_byte() == token::LIST_START
+    }
+
+    pub fn is_dict(&self) -> bool {
+        self.first_byte() == token::MAP_START
+    }
+
+    /// Returns the integer, if the value is an integer, that fits into
+    /// `i64`.
+    pub fn as_int(&self) -> Option<i64> {
+        if self.is_int() {
+            self.decode().ok()
+        } else {
+            None
+        }
+    }
+
+    /// Returns the raw bytes, if the value is a byte string.
+    pub fn as_bytes(&self) -> Option<&'a [u8]> {
+        if self.is_bytes() {
+            self.decode().ok()
+        } else {
+            None
+        }
+    }
+
+    /// Returns the string, if the value is a byte string containing valid
+    /// UTF-8.
+    pub fn as_str(&self) -> Option<&'a str> {
+        self.as_bytes()
+            .and_then(|bytes| core::str::from_utf8(bytes).ok())
+    }
+
+    /// Returns a value of a dictionary.
+    ///
+    /// Returns `None`, if the value is not a dictionary, or if the key does
+    /// not exist.
+    pub fn get<K>(&self, key: K) -> Option<Node<'d, 'a>>
+    where
+        K: AsRef<[u8]>,
+    {
+        let key = key.as_ref();
+
+        self.entries()
+            .find(|(k, _)| *k == key)
+            .map(|(_, value)| value)
+    }
+
+    /// Returns an element of a list.
+    ///
+    /// Returns `None`, if the value is not a list, or if the index is out of
+    /// bounds.
+    pub fn at(&self, index: usize) -> Option<Node<'d, 'a>> {
+        self.iter().nth(index)
+    }
+
+    /// Returns the number of elements of a list or entries of a dictionary,
+    /// zero for any other value.
+    pub fn len(&self) -> usize {
+        if self.is_dict() {
+            self.entries().count()
+        } else {
+            self.iter().count()
+        }
+    }
+
+    /// Returns `true`, if the value has no elements or entries, i.e. it is
+    /// an empty list or dictionary, or it is not a list or dictionary at all.
+    pub fn is_empty(&self) -> bool {
+        self.children().next().is_none()
+    }
+
+    /// Returns an iterator over the elements of a list, that is empty for
+    /// any other value.
+    pub fn iter(&self) -> Elements<'d, 'a> {
+        if self.is_list() {
+            self.children()
+        } else {
+            Elements::empty(self.document)
+        }
+    }
+
+    /// Returns an iterator over the keys & values of a dictionary, that is
+    /// empty for any other value.
+    pub fn entries(&self) -> Entries<'d, 'a> {
+        let elements = if self.is_dict() {
+            self.children()
+        } else {
+            Elements::empty(self.document)
+        };
+
+        Entries { elements }
+    }
+
+    fn children(&self) -> Elements<'d, 'a> {
+        Elements {
+            document: self.document,
+            index: self.index + 1,
+            end: self.span().next,
+        }
+    }
+}
+
+impl fmt::Debug for Node<'_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Node")
+            .field(&String::from_utf8_lossy(self.as_raw()))
+            .finish()
+    }
+}
+
+/// An iterator over the elements of a list.
+pub struct Elements<'d, 'a> {
+    document: &'d Document<'a>,
+    index: usize,
+    end: usize,
+}
+
+impl<'d, 'a> Elements<'d, 'a> {
+    fn empty(document: &'d Document<'a>) -> Self {
+        Self {
+            document,
+            index: 0,
+            end: 0,
+        }
+    }
+}
+
+impl<'d, 'a> Iterator for Elements<'d, 'a> {
+    type Item = Node<'d, 'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index >= self.end {
+            return None;
+        }
+
+        let node = Node {
+            document: self.document,
+            index: self.index,
+        };
+        self.index = node.span().next;
+
+        Some(node)
+    }
+}
+
+/// An iterator over the keys & values of a dictionary.
+pub struct Entries<'d, 'a> {
+    elements: Elements<'d, 'a>,
+}
+
+impl<'d, 'a> Iterator for Entries<'d, 'a> {
+    type Item = (&'a [u8], Node<'d, 'a>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let key = self.elements.next()?;
+        let value = self.elements.next()?;
+
+        Some((key.decode().ok()?, value))
+    }
+}
@@ -1,12 +1,30 @@
 //! Bencode errors and result type for serialization & deserialization.
 
-use std::fmt::Display;
+use alloc::{boxed::Box, string::ToString};
+use core::fmt::{self, Display};
 
 use serde::{de, ser};
 use thiserror::Error;
 
+/// An error, that occurred during serialization or deserialization.
+///
+/// Errors produced by the `Deserializer` carry the byte offset into the input
+/// & the path of the value (e.g. `info.files[3].length`), at which they have
+/// occurred. What went wrong is described by its `ErrorKind`, which used to
+/// be the `Error` itself, before 0.2.
+pub struct Error {
+    inner: Box<ErrorImpl>,
+}
+
+struct ErrorImpl {
+    kind: ErrorKind,
+    position: Option<usize>,
+    path: Option<Box<str>>,
+}
+
+/// Kinds of errors, that can occur during serialization or deserialization.
 #[derive(Debug, Error)]
-pub enum Error {
+pub enum ErrorKind {
     /// Catch-all for deserialization & serialization error messages.
     #[error("{0}")]
     Message(Box<str>),
@@ -71,11 +89,86 @@ pub enum Error {
     #[error("Expected dictionary length")]
     ExpectedDictionaryEnd,
 
+    /// ExpectedEnum occurs, when an enum (either a string of a unit variant,
+    /// or a dictionary of the other variants) was expected at the position
+    /// during deserialization.
+    #[error("Expected enum")]
+    ExpectedEnum,
+
     /// ExpectedDictionaryKeyString occurs, when dictionary's key has not been
     /// specified, or is of an unappropriate type during deserialization.
     #[error("Expected dictionary key")]
     ExpectedDictionaryKeyString,
 
+    /// KeyMustBeAString occurs, when a dictionary key is of a type, that can
+    /// not be represented as a byte string during serialization.
+    #[error("Dictionary key must be a string")]
+    KeyMustBeAString,
+
+    /// UnsupportedNone occurs, when `None` is serialized anywhere else than
+    /// as a value of a dictionary (e.g. as an element of a list), as Bencode
+    /// has no representation of a missing value.
+    #[error("None can only be serialized as a dictionary value")]
+    UnsupportedNone,
+
+    /// UnsupportedUnit occurs, when a unit (`()` or a unit struct) is
+    /// serialized anywhere else than as a value of a dictionary, the same way
+    /// as `None`.
+    #[error("Unit can only be serialized as a dictionary value")]
+    UnsupportedUnit,
+
+    /// UnsupportedBool occurs, when a `bool` is serialized or deserialized
+    /// with `BoolEncoding::Error`.
+    #[error("Unsupported bool")]
+    UnsupportedBool,
+
+    /// UnsupportedFloat occurs, when a float is serialized or deserialized
+    /// with `FloatEncoding::Error`, or when it can not be represented as a
+    /// fixed-point integer (e.g. `NaN`).
+    #[error("Unsupported float")]
+    UnsupportedFloat,
+
+    /// DuplicateKey occurs, when a dictionary contains the same key more than
+    /// once during serialization, or during deserialization in the strict mode.
+    #[error("Duplicate dictionary key")]
+    DuplicateKey,
+
+    /// UnsortedKeys occurs, when the keys of a dictionary are not sorted during
+    /// deserialization in the strict mode.
+    #[error("Unsorted dictionary keys")]
+    UnsortedKeys,
+
+    /// NonCanonicalInteger occurs, when an integer contains leading zeros, a
+    /// negative zero, or misplaced signs during deserialization in the strict
+    /// mode.
+    #[error("Non-canonical integer")]
+    NonCanonicalInteger,
+
+    /// NonCanonicalStringLength occurs, when a length of string contains
+    /// leading zeros during deserialization in the strict mode.
+    #[error("Non-canonical string length")]
+    NonCanonicalStringLength,
+
+    /// DepthLimitExceeded occurs, when lists & dictionaries are nested deeper
+    /// than allowed during deserialization.
+    #[error("Depth limit exceeded")]
+    DepthLimitExceeded,
+
+    /// StringTooLong occurs, when a length of string exceeds the allowed
+    /// maximum during deserialization.
+    #[error("String too long")]
+    StringTooLong,
+
+    /// TooManyItems occurs, when the input contains more list elements &
+    /// dictionary entries than allowed during deserialization.
+    #[error("Too many items")]
+    TooManyItems,
+
+    /// InputTooLarge occurs, when the size of the input exceeds the allowed
+    /// maximum during deserialization.
+    #[error("Input too large")]
+    InputTooLarge,
+
     /// UnknownType occurs, when the data is impossible to infer from during
     /// deserialization.
     #[error("Unknown type")]
@@ -93,19 +186,111 @@ pub enum Error {
 
     /// IO occurs, when caused by a failure to read or write bytes on an IO
     /// stream.
+    #[cfg(feature = "std")]
     #[error(transparent)]
-    IO(#[from] std::io::Error),
+    IO(std::io::Error),
+}
+
+pub type Result<T> = core::result::Result<T, Error>;
+
+impl Error {
+    /// Returns the kind of the error.
+    pub fn kind(&self) -> &ErrorKind {
+        &self.inner.kind
+    }
+
+    /// Consumes the error, returning its kind.
+    pub fn into_kind(self) -> ErrorKind {
+        self.inner.kind
+    }
+
+    /// Returns the byte offset into the input, at which the error occurred
+    /// during deserialization.
+    pub fn position(&self) -> Option<usize> {
+        self.inner.position
+    }
+
+    /// Returns the path of the value, at which the error occurred during
+    /// deserialization (e.g. `info.files[3].length`).
+    ///
+    /// Errors, that occurred at the top-level value, have no path.
+    pub fn path(&self) -> Option<&str> {
+        self.inner.path.as_deref()
+    }
+
+    /// Attaches the position & path to the error, unless it already has one.
+    pub(crate) fn locate<F>(mut self, position: usize, path: F) -> Self
+    where
+        F: FnOnce() -> Option<Box<str>>,
+    {
+        if self.inner.position.is_none() {
+            self.inner.position = Some(position);
+            self.inner.path = path();
+        }
+
+        self
+    }
+}
+
+impl From<ErrorKind> for Error {
+    fn from(kind: ErrorKind) -> Self {
+        Error {
+            inner: Box::new(ErrorImpl {
+                kind,
+                position: None,
+                path: None,
+            }),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<std::io::Error> for Error {
+    fn from(error: std::io::Error) -> Self {
+        ErrorKind::IO(error).into()
+    }
+}
+
+impl fmt::Debug for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Error")
+            .field("kind", &self.inner.kind)
+            .field("position", &self.inner.position)
+            .field("path", &self.inner.path)
+            .finish()
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Display::fmt(&self.inner.kind, f)?;
+
+        if let Some(position) = self.inner.position {
+            write!(f, " at byte {}", position)?;
+        }
+
+        if let Some(path) = &self.inner.path {
+            write!(f, " in `{}`", path)?;
+        }
+
+        Ok(())
+    }
 }
 
-pub type Result<T> = std::result::Result<T, Error>;
+impl core::error::Error for Error {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        core::error::Error::source(&self.inner.kind)
+    }
+}
 
 impl de::Error for Error {
     #[cold]
+    #[allow(clippy::multiple_bound_locations)]
     fn custom<T: Display>(msg: T) -> Self
     where
         T: Display,
     {
-        Error::Message(msg.to_string().into_boxed_str())
+        ErrorKind::Message(msg.to_string().into_boxed_str()).into()
     }
 
     #[cold]
@@ -120,10 +305,11 @@ impl de::Error for Error {
 
 impl ser::Error for Error {
     #[cold]
+    #[allow(clippy::multiple_bound_locations)]
     fn custom<T: Display>(msg: T) -> Self
     where
         T: Display,
     {
-        Error::Message(msg.to_string().into_boxed_str())
+        ErrorKind::Message(msg.to_string().into_boxed_str()).into()
     }
 }
@@ -0,0 +1,139 @@
+//! Preservation of unknown dictionary entries.
+
+use alloc::{
+    collections::{btree_map, BTreeMap},
+    vec::Vec,
+};
+use core::fmt;
+
+use crate::{
+    raw::RawValue,
+    value::{Key, KeyBuf},
+};
+
+use serde::{de, ser};
+
+/// Dictionary entries, that are not recognized by a struct, kept as raw
+/// values.
+///
+/// When used as a `#[serde(flatten)]` field, it collects every key of the
+/// dictionary, that does not match any other field of the struct, together
+/// with the bytes of its value. When serialized, the entries are written back
+/// among the other fields, in their canonical position. This makes it
+/// possible to load a `.torrent` into a typed struct & write it back, without
+/// losing any vendor-specific keys (e.g. `source`) & changing the info-hash.
+///
+/// The values are kept exactly as they appeared in the input, including the
+/// order of keys of nested dictionaries. `serde` buffers the values of a
+/// struct with a flattened field without their encoding, therefore a
+/// non-canonical integer or string length among them is rejected, instead of
+/// being written back differently.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Extra {
+    entries: BTreeMap<Vec<u8>, RawValue<'static>>,
+}
+
+impl Extra {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the raw value of a key.
+    pub fn get<K>(&self, key: K) -> Option<&RawValue<'static>>
+    where
+        K: AsRef<[u8]>,
+    {
+        self.entries.get(key.as_ref())
+    }
+
+    /// Inserts an entry, returning the previous value of the key.
+    pub fn insert<K>(&mut self, key: K, value: RawValue<'_>) -> Option<RawValue<'static>>
+    where
+        K: Into<Vec<u8>>,
+    {
+        self.entries.insert(key.into(), value.into_owned())
+    }
+
+    /// Removes an entry, returning the value of the key.
+    pub fn remove<K>(&mut self, key: K) -> Option<RawValue<'static>>
+    where
+        K: AsRef<[u8]>,
+    {
+        self.entries.remove(key.as_ref())
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns an iterator over the entries, sorted by their keys.
+    pub fn iter(&self) -> btree_map::Iter<'_, Vec<u8>, RawValue<'static>> {
+        self.entries.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Extra {
+    type Item = (&'a Vec<u8>, &'a RawValue<'static>);
+    type IntoIter = btree_map::Iter<'a, Vec<u8>, RawValue<'static>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl IntoIterator for Extra {
+    type Item = (Vec<u8>, RawValue<'static>);
+    type IntoIter = btree_map::IntoIter<Vec<u8>, RawValue<'static>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.into_iter()
+    }
+}
+
+//////////////////////////////////////////////////////
+
+impl ser::Serialize for Extra {
+    fn serialize<S>(&self, serializer: S) -> core::result::Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        serializer.collect_map(self.entries.iter().map(|(k, v)| (Key(k), v)))
+    }
+}
+
+impl<'de> de::Deserialize<'de> for Extra {
+    fn deserialize<D>(deserializer: D) -> core::result::Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        struct ExtraVisitor;
+
+        impl<'de> de::Visitor<'de> for ExtraVisitor {
+            type Value = Extra;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a bencode dictionary")
+            }
+
+            fn visit_map<A>(self, mut map: A) -> core::result::Result<Extra, A::Error>
+            where
+                A: de::MapAccess<'de>,
+            {
+                let mut extra = Extra::new();
+
+                while let Some(KeyBuf(key)) = map.next_key()? {
+                    let value = map.next_value::<RawValue>()?;
+                    extra.insert(key, value);
+                }
+
+                Ok(extra)
+            }
+        }
+
+        deserializer.deserialize_map(ExtraVisitor)
+    }
+}
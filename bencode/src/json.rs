@@ -0,0 +1,355 @@
+//! Lossless conversion between Bencode & JSON.
+//!
+//! Bencode values map onto JSON as follows:
+//!
+//! - integers are JSON numbers, unless they are out of the range of `i64` &
+//!   `u64`, which are tagged objects `{"$int": "..."}` with decimal digits,
+//! - byte strings containing valid UTF-8 are JSON strings, any other byte
+//!   strings are tagged objects `{"$hex": "..."}` (or `{"$base64": "..."}`),
+//! - lists are JSON arrays,
+//! - dictionaries are JSON objects.
+//!
+//! JSON objects hold their keys sorted & unique, the same way as canonical
+//! Bencode does. A dictionary, that has unsorted or duplicate keys, a key,
+//! that is not valid UTF-8, or that would be mistaken for a tagged object, is
+//! written as `{"$dict": [[key, value], ...]}` with the entries in their
+//! original order instead.
+
+use std::str;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::de::Error as _;
+use serde_json::{Map, Value as Json};
+
+use crate::{
+    document::{Document, Node},
+    error::{Error, ErrorKind, Result},
+    token,
+    value::Value,
+};
+
+const HEX_TAG: &str = "$hex";
+const BASE64_TAG: &str = "$base64";
+const DICT_TAG: &str = "$dict";
+const INT_TAG: &str = "$int";
+
+/// Encoding of byte strings, that are not valid UTF-8.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BytesEncoding {
+    /// Tagged object `{"$hex": "..."}` with lowercase hexadecimal digits.
+    #[default]
+    Hex,
+    /// Tagged object `{"$base64": "..."}` with the standard alphabet.
+    Base64,
+}
+
+/// Converts a Bencode document into JSON, encoding byte strings, that are
+/// not valid UTF-8, as hexadecimal.
+///
+/// The document is transcoded as it is, without going through `Value`,
+/// therefore the order & duplicates of dictionary keys and integers of any
+/// size are preserved.
+pub fn to_json(data: &[u8]) -> Result<Json> {
+    to_json_with(data, BytesEncoding::Hex)
+}
+
+/// Converts a Bencode document into JSON, encoding byte strings, that are
+/// not valid UTF-8, with the given encoding.
+pub fn to_json_with(data: &[u8], encoding: BytesEncoding) -> Result<Json> {
+    node_to_json(Document::new(data)?.root(), encoding)
+}
+
+/// Converts JSON, as produced by `to_json`, back into a Bencode document.
+///
+/// Dictionaries tagged as `$dict` are written with their entries in the
+/// given order, therefore a document, that has been converted by `to_json`,
+/// is encoded back byte by byte, unless it contains non-canonical integers
+/// or string lengths (e.g. `i05e`).
+pub fn from_json(json: &Json) -> Result<Vec<u8>> {
+    let mut output = Vec::new();
+    write_json(&mut output, json)?;
+
+    Ok(output)
+}
+
+impl Value {
+    /// Converts the value into JSON, encoding byte strings, that are not
+    /// valid UTF-8, as hexadecimal.
+    pub fn to_json(&self) -> Json {
+        self.to_json_with(BytesEncoding::Hex)
+    }
+
+    /// Converts the value into JSON, encoding byte strings, that are not
+    /// valid UTF-8, with the given encoding.
+    pub fn to_json_with(&self, encoding: BytesEncoding) -> Json {
+        match self {
+            Value::Integer(value) => integer_to_json(*value),
+            Value::Bytes(bytes) => bytes_to_json(bytes, encoding),
+            Value::List(list) => list.iter().map(|v| v.to_json_with(encoding)).collect(),
+            Value::Dict(dict) => dict_to_json(
+                dict.iter()
+                    .map(|(key, value)| (key.as_slice(), value.to_json_with(encoding)))
+                    .collect(),
+                encoding,
+            ),
+        }
+    }
+
+    /// Converts JSON, as produced by `to_json`, back into a value.
+    ///
+    /// Both of the byte string encodings are accepted. Floats, booleans &
+    /// nulls have no Bencode representation & result in an error.
+    pub fn from_json(json: &Json) -> Result<Value> {
+        match json {
+            Json::Number(number) => match (number.as_i64(), number.as_u64()) {
+                (Some(value), _) => Ok(Value::from(value)),
+                (_, Some(value)) => Ok(Value::from(value)),
+                _ => Err(Error::custom(format!("unsupported JSON number {}", number))),
+            },
+            Json::String(string) => Ok(Value::from(string.as_str())),
+            Json::Array(array) => array
+                .iter()
+                .map(Value::from_json)
+                .collect::<Result<Vec<_>>>()
+                .map(Value::List),
+            Json::Object(map) => match single_entry(map) {
+                Some((HEX_TAG | BASE64_TAG, _)) => json_to_bytes(json).map(Value::Bytes),
+                Some((DICT_TAG, Json::Array(pairs))) => pairs
+                    .iter()
+                    .map(|pair| match pair.as_array().map(Vec::as_slice) {
+                        Some([key, value]) => Ok((json_to_bytes(key)?, Value::from_json(value)?)),
+                        _ => Err(Error::custom("expected a [key, value] pair")),
+                    })
+                    .collect::<Result<_>>()
+                    .map(Value::Dict),
+                Some((DICT_TAG, _)) => Err(Error::custom("expected an array of pairs")),
+                Some((INT_TAG, Json::String(digits))) => digits
+                    .parse()
+                    .map(Value::Integer)
+                    .map_err(|e| Error::custom(format!("invalid integer: {}", e))),
+                Some((INT_TAG, _)) => Err(Error::custom("expected a string of digits")),
+                _ => map
+                    .iter()
+                    .map(|(key, value)| Ok((key.as_bytes().to_vec(), Value::from_json(value)?)))
+                    .collect::<Result<_>>()
+                    .map(Value::Dict),
+            },
+            Json::Bool(_) => Err(Error::custom("unsupported JSON boolean")),
+            Json::Null => Err(Error::custom("unsupported JSON null")),
+        }
+    }
+}
+
+/// Converts a value of a document into JSON.
+fn node_to_json(node: Node, encoding: BytesEncoding) -> Result<Json> {
+    if let Some(bytes) = node.as_bytes() {
+        Ok(bytes_to_json(bytes, encoding))
+    } else if node.is_list() {
+        node.iter()
+            .map(|element| node_to_json(element, encoding))
+            .collect()
+    } else if node.is_dict() {
+        let entries = node
+            .entries()
+            .map(|(key, value)| Ok((key, node_to_json(value, encoding)?)))
+            .collect::<Result<_>>()?;
+
+        Ok(dict_to_json(entries, encoding))
+    } else {
+        // The integer has been validated by the document, therefore it
+        // consists of ASCII characters only.
+        let raw = node.as_raw();
+        let digits = str::from_utf8(&raw[1..raw.len() - 1]).unwrap_or_default();
+
+        digits_to_json(digits)
+    }
+}
+
+/// Converts the entries of a dictionary into a JSON object, unless the
+/// object would lose any of the entries or their order.
+fn dict_to_json(entries: Vec<(&[u8], Json)>, encoding: BytesEncoding) -> Json {
+    let sorted = entries.windows(2).all(|pair| pair[0].0 < pair[1].0);
+    let keys = entries
+        .iter()
+        .map(|(key, _)| str::from_utf8(key).ok())
+        .collect::<Option<Vec<_>>>();
+
+    match keys {
+        Some(keys) if sorted && !(keys.len() == 1 && is_tag(keys[0])) => Json::Object(
+            keys.into_iter()
+                .map(str::to_owned)
+                .zip(entries.into_iter().map(|(_, value)| value))
+                .collect(),
+        ),
+        _ => {
+            let pairs = entries
+                .into_iter()
+                .map(|(key, value)| Json::Array(vec![bytes_to_json(key, encoding), value]))
+                .collect();
+
+            tagged(DICT_TAG, Json::Array(pairs))
+        }
+    }
+}
+
+/// Writes JSON, as produced by `to_json`, as Bencode.
+fn write_json(output: &mut Vec<u8>, json: &Json) -> Result<()> {
+    match json {
+        Json::Number(number) => match (number.as_i64(), number.as_u64()) {
+            (Some(value), _) => write_integer(output, &value.to_string()),
+            (_, Some(value)) => write_integer(output, &value.to_string()),
+            _ => Err(Error::custom(format!("unsupported JSON number {}", number))),
+        },
+        Json::String(string) => {
+            write_bytes(output, string.as_bytes());
+            Ok(())
+        }
+        Json::Array(array) => {
+            output.push(token::LIST_START);
+            for element in array {
+                write_json(output, element)?;
+            }
+            output.push(token::END);
+
+            Ok(())
+        }
+        Json::Object(map) => match single_entry(map) {
+            Some((HEX_TAG | BASE64_TAG, _)) => {
+                write_bytes(output, &json_to_bytes(json)?);
+                Ok(())
+            }
+            Some((INT_TAG, Json::String(digits))) => write_integer(output, digits),
+            Some((INT_TAG, _)) => Err(Error::custom("expected a string of digits")),
+            Some((DICT_TAG, Json::Array(pairs))) => {
+                output.push(token::MAP_START);
+                for pair in pairs {
+                    match pair.as_array().map(Vec::as_slice) {
+                        Some([key, value]) => {
+                            write_bytes(output, &json_to_bytes(key)?);
+                            write_json(output, value)?;
+                        }
+                        _ => return Err(Error::custom("expected a [key, value] pair")),
+                    }
+                }
+                output.push(token::END);
+
+                Ok(())
+            }
+            Some((DICT_TAG, _)) => Err(Error::custom("expected an array of pairs")),
+            _ => {
+                // The keys are sorted, unless the map preserves the order of
+                // insertion.
+                let mut entries = map.iter().collect::<Vec<_>>();
+                entries.sort_by(|(a, _), (b, _)| a.as_bytes().cmp(b.as_bytes()));
+
+                output.push(token::MAP_START);
+                for (key, value) in entries {
+                    write_bytes(output, key.as_bytes());
+                    write_json(output, value)?;
+                }
+                output.push(token::END);
+
+                Ok(())
+            }
+        },
+        Json::Bool(_) => Err(Error::custom("unsupported JSON boolean")),
+        Json::Null => Err(Error::custom("unsupported JSON null")),
+    }
+}
+
+fn write_integer(output: &mut Vec<u8>, digits: &str) -> Result<()> {
+    if !is_integer(digits) {
+        return Err(Error::custom(format!("invalid integer: {}", digits)));
+    }
+
+    output.push(token::INTEGER_START);
+    output.extend_from_slice(digits.as_bytes());
+    output.push(token::END);
+
+    Ok(())
+}
+
+fn write_bytes(output: &mut Vec<u8>, bytes: &[u8]) {
+    output.extend_from_slice(bytes.len().to_string().as_bytes());
+    output.push(token::BYTES_DELIMITER);
+    output.extend_from_slice(bytes);
+}
+
+/// Checks, that the digits are an integer, i.e. an optional minus sign
+/// followed by at least one digit.
+fn is_integer(digits: &str) -> bool {
+    let digits = digits.strip_prefix('-').unwrap_or(digits);
+
+    !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit())
+}
+
+fn is_tag(key: &str) -> bool {
+    matches!(key, HEX_TAG | BASE64_TAG | DICT_TAG | INT_TAG)
+}
+
+fn tagged(tag: &str, value: Json) -> Json {
+    let mut map = Map::new();
+    map.insert(tag.to_owned(), value);
+
+    Json::Object(map)
+}
+
+fn single_entry(map: &Map<String, Json>) -> Option<(&str, &Json)> {
+    if map.len() == 1 {
+        map.iter().next().map(|(key, value)| (key.as_str(), value))
+    } else {
+        None
+    }
+}
+
+fn integer_to_json(value: i128) -> Json {
+    if let Ok(value) = i64::try_from(value) {
+        Json::from(value)
+    } else if let Ok(value) = u64::try_from(value) {
+        Json::from(value)
+    } else {
+        tagged(INT_TAG, Json::from(value.to_string()))
+    }
+}
+
+/// Converts the digits of an integer of any size into JSON.
+fn digits_to_json(digits: &str) -> Result<Json> {
+    if let Ok(value) = digits.parse::<i64>() {
+        Ok(Json::from(value))
+    } else if let Ok(value) = digits.parse::<u64>() {
+        Ok(Json::from(value))
+    } else if is_integer(digits) {
+        Ok(tagged(INT_TAG, Json::from(digits)))
+    } else {
+        Err(ErrorKind::ExpectedSignedNumber.into())
+    }
+}
+
+fn bytes_to_json(bytes: &[u8], encoding: BytesEncoding) -> Json {
+    match str::from_utf8(bytes) {
+        Ok(string) => Json::from(string),
+        Err(_) => match encoding {
+            BytesEncoding::Hex => tagged(HEX_TAG, Json::from(hex::encode(bytes))),
+            BytesEncoding::Base64 => tagged(BASE64_TAG, Json::from(STANDARD.encode(bytes))),
+        },
+    }
+}
+
+/// Converts a JSON string or a tagged object into a byte string.
+fn json_to_bytes(json: &Json) -> Result<Vec<u8>> {
+    if let Json::String(string) = json {
+        return Ok(string.as_bytes().to_vec());
+    }
+
+    let tag = json.as_object().and_then(single_entry);
+
+    match tag {
+        Some((HEX_TAG, Json::String(digits))) => {
+            hex::decode(digits).map_err(|e| Error::custom(format!("invalid hex: {}", e)))
+        }
+        Some((BASE64_TAG, Json::String(digits))) => STANDARD
+            .decode(digits)
+            .map_err(|e| Error::custom(format!("invalid base64: {}", e))),
+        _ => Err(Error::custom("expected a string or a tagged byte string")),
+    }
+}
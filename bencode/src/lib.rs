@@ -1,14 +1,96 @@
+//! Bencode serialization & deserialization with `serde`.
+//!
+//! # Breaking changes in 0.2
+//!
+//! `Error` is no longer an enum, but a struct, that carries the position &
+//! the path of a deserialization error. The former variants are now the
+//! variants of `ErrorKind`, therefore a `match` on an `Error` becomes a
+//! `match` on `error.kind()`, or on `error.into_kind()`, for example:
+//!
+//! ```
+//! use bitrust_bencode::{from_str, Error, ErrorKind};
+//!
+//! match from_str::<i64>("i1").map_err(Error::into_kind) {
+//!     Err(ErrorKind::ExpectedIntegerEnd) => {}
+//!     other => panic!("{:?}", other),
+//! }
+//! ```
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+#[macro_use]
+mod macros;
 mod token;
 
+pub mod bytes;
+#[cfg(feature = "tokio")]
+pub mod codec;
 pub mod de;
+#[cfg(feature = "derive")]
+mod derive;
+pub mod document;
 pub mod error;
+pub mod extra;
+#[cfg(feature = "json")]
+pub mod json;
+pub mod push;
+pub mod raw;
 pub mod ser;
+pub mod tokenizer;
+pub mod value;
+
+#[cfg(feature = "std")]
+#[doc(inline)]
+pub use self::de::{from_reader, from_reader_with_options};
+
+#[doc(inline)]
+pub use self::de::{
+    from_slice, from_slice_prefix, from_slice_strict, from_slice_with_options, from_str,
+    Deserializer, DeserializerOptions, StreamDeserializer,
+};
+
+#[doc(inline)]
+pub use self::ser::{
+    to_string, to_vec, to_vec_with_options, to_writer, to_writer_with_options, BoolEncoding,
+    FloatEncoding, Serializer, SerializerOptions,
+};
+
+#[doc(inline)]
+pub use self::document::{Document, Node};
 
 #[doc(inline)]
-pub use self::de::{from_slice, from_str, Deserializer};
+pub use self::extra::Extra;
 
 #[doc(inline)]
-pub use self::ser::{to_string, to_vec, Serializer};
+pub use self::push::{value_length, value_length_with_options, PushParser, Status};
 
 #[doc(inline)]
-pub use self::error::{Error, Result};
+pub use self::raw::RawValue;
+
+#[doc(inline)]
+pub use self::tokenizer::{Event, Tokenizer};
+
+#[doc(inline)]
+pub use self::value::{from_value, to_value, DictKey, Value};
+
+#[cfg(feature = "tokio")]
+#[doc(inline)]
+pub use self::codec::BencodeCodec;
+
+#[cfg(feature = "derive")]
+pub use bitrust_bencode_derive::Bencode;
+
+#[doc(inline)]
+pub use self::error::{Error, ErrorKind, Result};
+
+#[doc(hidden)]
+pub mod __private {
+    pub use alloc::{collections::BTreeMap, vec, vec::Vec};
+
+    #[cfg(feature = "derive")]
+    pub use crate::derive::{FieldKey, IntBool};
+    #[cfg(feature = "derive")]
+    pub use serde;
+}
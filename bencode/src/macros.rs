@@ -0,0 +1,84 @@
+//! Construction of `Value`s with a literal syntax.
+
+/// Builds a `Value` from a literal, similar to the `json!` macro of
+/// `serde_json`.
+///
+/// Dictionaries are written as `{ key => value, ... }` & lists as
+/// `[value, ...]`, both of them may be nested. The braces of a top-level
+/// dictionary may be omitted, i.e. `bencode!{ "y" => "q" }`. Any other value is an
+/// expression, that is converted with `Value::from`, e.g. an integer, a
+/// string, a byte string literal `b"aa"`, a `[u8; 20]` node ID or another
+/// `Value`.
+///
+/// Keys are expressions of a type implementing `DictKey`, i.e. strings &
+/// byte strings. Any other key, such as an integer, does not compile. When
+/// a key is given more than once, the last value is kept.
+#[macro_export]
+macro_rules! bencode {
+    // Elements of a list, accumulated into `[$done, ...]`.
+    (@list [$($done:expr,)*]) => {
+        $crate::__private::vec![$($done,)*]
+    };
+
+    (@list [$($done:expr,)*] [ $($list:tt)* ] $(, $($rest:tt)*)?) => {
+        $crate::bencode!(@list [$($done,)* $crate::bencode!([$($list)*]),] $($($rest)*)?)
+    };
+
+    (@list [$($done:expr,)*] { $($dict:tt)* } $(, $($rest:tt)*)?) => {
+        $crate::bencode!(@list [$($done,)* $crate::bencode!({$($dict)*}),] $($($rest)*)?)
+    };
+
+    (@list [$($done:expr,)*] $value:expr $(, $($rest:tt)*)?) => {
+        $crate::bencode!(@list [$($done,)* $crate::bencode!($value),] $($($rest)*)?)
+    };
+
+    // Entries of a dictionary, inserted one by one.
+    (@dict $dict:ident) => {};
+
+    (@dict $dict:ident $key:expr => [ $($list:tt)* ] $(, $($rest:tt)*)?) => {
+        $crate::bencode!(@insert $dict $key, $crate::bencode!([$($list)*]));
+        $crate::bencode!(@dict $dict $($($rest)*)?);
+    };
+
+    (@dict $dict:ident $key:expr => { $($inner:tt)* } $(, $($rest:tt)*)?) => {
+        $crate::bencode!(@insert $dict $key, $crate::bencode!({$($inner)*}));
+        $crate::bencode!(@dict $dict $($($rest)*)?);
+    };
+
+    (@dict $dict:ident $key:expr => $value:expr $(, $($rest:tt)*)?) => {
+        $crate::bencode!(@insert $dict $key, $crate::bencode!($value));
+        $crate::bencode!(@dict $dict $($($rest)*)?);
+    };
+
+    (@insert $dict:ident $key:expr, $value:expr) => {
+        $dict.insert($crate::DictKey::to_key(&$key), $value)
+    };
+
+    ([]) => {
+        $crate::Value::List($crate::__private::Vec::new())
+    };
+
+    ([ $($elements:tt)+ ]) => {
+        $crate::Value::List($crate::bencode!(@list [] $($elements)+))
+    };
+
+    ({}) => {
+        $crate::Value::Dict($crate::__private::BTreeMap::new())
+    };
+
+    ({ $($entries:tt)+ }) => {{
+        let mut dict = $crate::__private::BTreeMap::new();
+        $crate::bencode!(@dict dict $($entries)+);
+        $crate::Value::Dict(dict)
+    }};
+
+    // Entries of a dictionary given without the braces, as in
+    // `bencode!{ "y" => "q" }`.
+    ($key:expr => $($rest:tt)*) => {
+        $crate::bencode!({ $key => $($rest)* })
+    };
+
+    ($value:expr) => {
+        $crate::Value::from($value)
+    };
+}
@@ -0,0 +1,340 @@
+//! Incremental parsing of Bencode, that arrives in chunks.
+
+use alloc::vec::Vec;
+
+use crate::{
+    de::{check_signed_number, consume_unsigned_number, Deserializer, DeserializerOptions},
+    error::{ErrorKind, Result},
+    token,
+};
+
+use serde::de;
+
+/// Progress of parsing a single Bencode value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    /// The value is not complete yet & at least the given number of bytes
+    /// is needed to continue.
+    NeedMore(usize),
+    /// The value is complete & spans the given number of bytes from the
+    /// start of the input. Any bytes after it are not part of the value.
+    Complete(usize),
+}
+
+/// What the scanner expects to read next.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Expect {
+    Value,
+    Key,
+    Nothing,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Container {
+    List,
+    Dict,
+}
+
+/// A resumable scanner of the structure of a single Bencode value.
+///
+/// The scanner advances over complete tokens only. An incomplete token at
+/// the end of the input is scanned again, once more data is available. Byte
+/// strings are not scanned byte by byte, therefore their content is never
+/// visited again.
+#[derive(Debug, Clone)]
+pub(crate) struct Scanner {
+    options: DeserializerOptions,
+    position: usize,
+    stack: Vec<Container>,
+    expect: Expect,
+}
+
+impl Scanner {
+    pub(crate) fn new(options: DeserializerOptions) -> Self {
+        Self {
+            options,
+            position: 0,
+            stack: Vec::new(),
+            expect: Expect::Value,
+        }
+    }
+
+    /// Scans the input, that starts with the part scanned by the previous
+    /// calls.
+    pub(crate) fn scan(&mut self, input: &[u8]) -> Result<Status> {
+        while self.expect != Expect::Nothing {
+            let data = &input[self.position..];
+
+            let consumed = match self.scan_token(data) {
+                Ok(Status::Complete(consumed)) => consumed,
+                Ok(Status::NeedMore(needed)) => {
+                    if input.len().saturating_add(needed) > self.options.max_input_size {
+                        return Err(ErrorKind::InputTooLarge.into());
+                    }
+
+                    return Ok(Status::NeedMore(needed));
+                }
+                Err(e) => return Err(e.locate(self.position, || None)),
+            };
+
+            self.position += consumed;
+        }
+
+        Ok(Status::Complete(self.position))
+    }
+
+    /// Scans a single token & updates the state accordingly.
+    fn scan_token(&mut self, data: &[u8]) -> Result<Status> {
+        let byte = match data.first() {
+            Some(&byte) => byte,
+            None => return Ok(Status::NeedMore(1)),
+        };
+
+        match (self.expect, byte) {
+            (Expect::Key, b'0'..=b'9') => {
+                let status = scan_bytes(data, self.options.max_string_length)?;
+
+                if let Status::Complete(_) = status {
+                    self.expect = Expect::Value;
+                }
+
+                Ok(status)
+            }
+            (Expect::Key, token::END) => {
+                self.stack.pop();
+                self.finish_value();
+
+                Ok(Status::Complete(1))
+            }
+            (Expect::Key, _) => Err(ErrorKind::ExpectedDictionaryKeyString.into()),
+            (_, b'0'..=b'9') => {
+                let status = scan_bytes(data, self.options.max_string_length)?;
+
+                if let Status::Complete(_) = status {
+                    self.finish_value();
+                }
+
+                Ok(status)
+            }
+            (_, token::INTEGER_START) => {
+                let status = scan_integer(data)?;
+
+                if let Status::Complete(_) = status {
+                    self.finish_value();
+                }
+
+                Ok(status)
+            }
+            (_, token::LIST_START) => {
+                self.enter(Container::List)?;
+                self.expect = Expect::Value;
+
+                Ok(Status::Complete(1))
+            }
+            (_, token::MAP_START) => {
+                self.enter(Container::Dict)?;
+                self.expect = Expect::Key;
+
+                Ok(Status::Complete(1))
+            }
+            (_, token::END) if self.stack.last() == Some(&Container::List) => {
+                self.stack.pop();
+                self.finish_value();
+
+                Ok(Status::Complete(1))
+            }
+            _ => Err(ErrorKind::UnknownType.into()),
+        }
+    }
+
+    fn enter(&mut self, container: Container) -> Result<()> {
+        if self.stack.len() >= self.options.max_depth {
+            return Err(ErrorKind::DepthLimitExceeded.into());
+        }
+
+        self.stack.push(container);
+
+        Ok(())
+    }
+
+    fn finish_value(&mut self) {
+        self.expect = match self.stack.last() {
+            None => Expect::Nothing,
+            Some(Container::List) => Expect::Value,
+            Some(Container::Dict) => Expect::Key,
+        };
+    }
+}
+
+/// Scans an integer `i<number>e`, checking its syntax the same way as
+/// skipped values are checked by the `Deserializer`. The number is not
+/// parsed, therefore it is not limited in size.
+fn scan_integer(data: &[u8]) -> Result<Status> {
+    let number = &data[1..];
+    let length = number
+        .iter()
+        .take_while(|byte| token::SIGNED_NUMBER_CHARSET.contains(byte))
+        .count();
+
+    match number.get(length) {
+        None => Ok(Status::NeedMore(1)),
+        Some(_) if length == 0 => Err(ErrorKind::ExpectedSignedNumber.into()),
+        Some(&token::END) => {
+            check_signed_number(&number[..length])?;
+
+            Ok(Status::Complete(length + 2))
+        }
+        Some(_) => Err(ErrorKind::ExpectedIntegerEnd.into()),
+    }
+}
+
+/// Scans a byte string `<length>:<bytes>`.
+fn scan_bytes(data: &[u8], max_length: usize) -> Result<Status> {
+    let digits = data.iter().take_while(|byte| byte.is_ascii_digit()).count();
+
+    match data.get(digits) {
+        None => return Ok(Status::NeedMore(1)),
+        Some(&token::BYTES_DELIMITER) => {}
+        Some(_) => return Err(ErrorKind::ExpectedStringIntegerLength.into()),
+    }
+
+    let (_, count) = consume_unsigned_number::<usize>(&data[..digits])?;
+
+    if count > max_length {
+        return Err(ErrorKind::StringTooLong.into());
+    }
+
+    let header = digits + 1;
+    let available = data.len() - header;
+
+    if available < count {
+        Ok(Status::NeedMore(count - available))
+    } else {
+        Ok(Status::Complete(header + count))
+    }
+}
+
+/// Reports the length of the first complete Bencode value in the input, or
+/// how many more bytes are needed at least, if it is not complete.
+///
+/// The syntax of the value is checked the same way as the `Deserializer`
+/// checks skipped values, but the value is not decoded, so that the bytes
+/// after it can be handled separately.
+///
+/// The default options do not limit the size of the input, therefore the
+/// number of bytes needed is the one claimed by the input & can be as large
+/// as `usize::MAX`. Use `value_length_with_options` to limit it, before
+/// reserving space for the bytes.
+pub fn value_length(data: &[u8]) -> Result<Status> {
+    value_length_with_options(data, DeserializerOptions::default())
+}
+
+/// Reports the length of the first complete Bencode value in the input, like
+/// `value_length`, but with the given options.
+///
+/// Fails with `InputTooLarge`, if the value would exceed `max_input_size`.
+pub fn value_length_with_options(data: &[u8], options: DeserializerOptions) -> Result<Status> {
+    Scanner::new(options).scan(data)
+}
+
+/// A parser, that is fed with chunks of data as they arrive & reports, when
+/// a single Bencode value is complete.
+///
+/// Parsing resumes where the previous chunk ended, instead of starting from
+/// the beginning of the value. The chunks are buffered until the value is
+/// complete & can be decoded.
+#[derive(Debug, Clone)]
+pub struct PushParser {
+    buffer: Vec<u8>,
+    scanner: Scanner,
+    status: Status,
+}
+
+impl PushParser {
+    /// Creates a parser with the default options.
+    pub fn new() -> Self {
+        Self::with_options(DeserializerOptions::default())
+    }
+
+    /// Creates a parser with the given options.
+    ///
+    /// The depth, string length & input size limits are enforced while the
+    /// data is fed, the rest once the value is decoded.
+    pub fn with_options(options: DeserializerOptions) -> Self {
+        Self {
+            buffer: Vec::new(),
+            scanner: Scanner::new(options),
+            status: Status::NeedMore(1),
+        }
+    }
+
+    /// Appends a chunk of data & continues parsing.
+    ///
+    /// Once the value is complete, any data fed afterwards is only buffered.
+    pub fn feed(&mut self, data: &[u8]) -> Result<Status> {
+        self.buffer.extend_from_slice(data);
+
+        if let Status::NeedMore(_) = self.status {
+            self.status = self.scanner.scan(&self.buffer)?;
+        }
+
+        Ok(self.status)
+    }
+
+    /// Returns the current status of parsing.
+    pub fn status(&self) -> Status {
+        self.status
+    }
+
+    /// Returns the bytes of the value, if it is complete.
+    pub fn value(&self) -> Option<&[u8]> {
+        match self.status {
+            Status::Complete(length) => Some(&self.buffer[..length]),
+            Status::NeedMore(_) => None,
+        }
+    }
+
+    /// Returns the bytes buffered after the complete value.
+    pub fn remaining(&self) -> &[u8] {
+        match self.status {
+            Status::Complete(length) => &self.buffer[length..],
+            Status::NeedMore(_) => &[],
+        }
+    }
+
+    /// Deserializes the complete value into a type `T`.
+    ///
+    /// Returns `ErrorKind::EOF` if the value is not complete yet.
+    pub fn decode<'de, T>(&'de self) -> Result<T>
+    where
+        T: de::Deserialize<'de>,
+    {
+        let value = self.value().ok_or(ErrorKind::EOF)?;
+
+        let mut de = Deserializer::with_options(value, self.scanner.options);
+        let value = T::deserialize(&mut de);
+
+        de.locate(value)
+    }
+
+    /// Starts parsing of the next value, keeping the bytes buffered after
+    /// the complete value. An incomplete value is discarded.
+    pub fn reset(&mut self) -> Result<Status> {
+        if let Status::Complete(length) = self.status {
+            self.buffer.drain(..length);
+        } else {
+            self.buffer.clear();
+        }
+
+        self.scanner = Scanner::new(self.scanner.options);
+        self.status = Status::NeedMore(1);
+
+        self.feed(&[])
+    }
+}
+
+impl Default for PushParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,282 @@
+//! Raw, undecoded Bencode value.
+
+use alloc::{borrow::Cow, string::String, vec::Vec};
+use core::fmt;
+
+use crate::{
+    de::{from_slice, Deserializer},
+    error::Result,
+    token,
+};
+
+use lexical::{ToLexical, BUFFER_SIZE};
+use serde::{de, ser};
+
+/// Name of the newtype struct, that the `Deserializer` & `Serializer`
+/// recognize as a raw value.
+pub(crate) const TOKEN: &str = "$bitrust_bencode::private::RawValue";
+
+/// A structure that holds a single Bencode value exactly as it appeared in
+/// the input, without decoding it.
+///
+/// When used as a field of a deserialized struct, the `Deserializer` borrows
+/// the original byte span of the value from the input. This makes it possible
+/// to compute hashes over the original bytes (e.g. the info-hash of a torrent)
+/// and to decode the value later on, if needed.
+///
+/// When serialized with the `Serializer`, the bytes are written as they are.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct RawValue<'a> {
+    bytes: Cow<'a, [u8]>,
+}
+
+impl<'a> RawValue<'a> {
+    /// Creates a raw value from a byte slice, checking that it contains
+    /// exactly one valid Bencode value.
+    pub fn from_slice(bytes: &'a [u8]) -> Result<Self> {
+        let mut de = Deserializer::new(bytes);
+        let result = de.skip_value();
+        de.locate(result)?;
+        de.end()?;
+
+        Ok(Self {
+            bytes: Cow::Borrowed(bytes),
+        })
+    }
+
+    /// Creates a raw value from a byte slice, that is known to contain exactly
+    /// one valid Bencode value.
+    pub(crate) fn borrowed(bytes: &'a [u8]) -> Self {
+        Self {
+            bytes: Cow::Borrowed(bytes),
+        }
+    }
+
+    /// Returns the raw bytes of the value.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Deserializes the raw bytes into a type `T`.
+    pub fn decode<'de, T>(&'de self) -> Result<T>
+    where
+        T: de::Deserialize<'de>,
+    {
+        from_slice(&self.bytes)
+    }
+
+    /// Converts the raw value into one, that owns its bytes.
+    pub fn into_owned(self) -> RawValue<'static> {
+        RawValue {
+            bytes: Cow::Owned(self.bytes.into_owned()),
+        }
+    }
+}
+
+impl fmt::Debug for RawValue<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("RawValue")
+            .field(&String::from_utf8_lossy(&self.bytes))
+            .finish()
+    }
+}
+
+//////////////////////////////////////////////////////
+
+struct RawBytes<'a>(&'a [u8]);
+
+impl ser::Serialize for RawBytes<'_> {
+    fn serialize<S>(&self, serializer: S) -> core::result::Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        serializer.serialize_bytes(self.0)
+    }
+}
+
+impl ser::Serialize for RawValue<'_> {
+    fn serialize<S>(&self, serializer: S) -> core::result::Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        serializer.serialize_newtype_struct(TOKEN, &RawBytes(&self.bytes))
+    }
+}
+
+impl<'de: 'a, 'a> de::Deserialize<'de> for RawValue<'a> {
+    fn deserialize<D>(deserializer: D) -> core::result::Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        struct RawValueVisitor;
+
+        impl<'de> de::Visitor<'de> for RawValueVisitor {
+            type Value = RawValue<'de>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("any valid bencode value")
+            }
+
+            fn visit_borrowed_bytes<E>(
+                self,
+                value: &'de [u8],
+            ) -> core::result::Result<Self::Value, E> {
+                Ok(RawValue {
+                    bytes: Cow::Borrowed(value),
+                })
+            }
+
+            fn visit_bytes<E>(self, value: &[u8]) -> core::result::Result<Self::Value, E> {
+                Ok(RawValue {
+                    bytes: Cow::Owned(value.to_vec()),
+                })
+            }
+
+            fn visit_byte_buf<E>(self, value: Vec<u8>) -> core::result::Result<Self::Value, E> {
+                Ok(RawValue {
+                    bytes: Cow::Owned(value),
+                })
+            }
+
+            /// Deserializers other than the Bencode one do not have access
+            /// to the original bytes, therefore the value is encoded back
+            /// into Bencode instead.
+            fn visit_newtype_struct<D>(
+                self,
+                deserializer: D,
+            ) -> core::result::Result<Self::Value, D::Error>
+            where
+                D: de::Deserializer<'de>,
+            {
+                Ok(RawValue {
+                    bytes: Cow::Owned(encode(deserializer)?),
+                })
+            }
+        }
+
+        deserializer.deserialize_newtype_struct(TOKEN, RawValueVisitor)
+    }
+}
+
+/// Encodes a value of any self-describing deserializer back into Bencode.
+///
+/// The elements of lists & the entries of dictionaries are written in the
+/// order they are visited, therefore a value buffered by `serde` (e.g. by
+/// `#[serde(flatten)]`) is written exactly as it appeared in the Bencode
+/// input. The `Deserializer` rejects non-canonical integers & lengths in
+/// such values.
+pub(crate) fn encode<'de, D>(deserializer: D) -> core::result::Result<Vec<u8>, D::Error>
+where
+    D: de::Deserializer<'de>,
+{
+    let mut output = Vec::new();
+    de::DeserializeSeed::deserialize(Encoder(&mut output), deserializer)?;
+
+    Ok(output)
+}
+
+struct Encoder<'o>(&'o mut Vec<u8>);
+
+impl Encoder<'_> {
+    fn write_number<T>(&mut self, value: T)
+    where
+        T: ToLexical,
+    {
+        let mut buffer = [0u8; BUFFER_SIZE];
+        self.0.extend_from_slice(value.to_lexical(&mut buffer));
+    }
+
+    fn write_integer<T>(&mut self, value: T)
+    where
+        T: ToLexical,
+    {
+        self.0.push(token::INTEGER_START);
+        self.write_number(value);
+        self.0.push(token::END);
+    }
+}
+
+impl<'de> de::DeserializeSeed<'de> for Encoder<'_> {
+    type Value = ();
+
+    fn deserialize<D>(self, deserializer: D) -> core::result::Result<(), D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> de::Visitor<'de> for Encoder<'_> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("any valid bencode value")
+    }
+
+    fn visit_i64<E>(mut self, value: i64) -> core::result::Result<(), E> {
+        self.write_integer(value);
+        Ok(())
+    }
+
+    fn visit_u64<E>(mut self, value: u64) -> core::result::Result<(), E> {
+        self.write_integer(value);
+        Ok(())
+    }
+
+    fn visit_i128<E>(mut self, value: i128) -> core::result::Result<(), E> {
+        self.write_integer(value);
+        Ok(())
+    }
+
+    fn visit_u128<E>(mut self, value: u128) -> core::result::Result<(), E> {
+        self.write_integer(value);
+        Ok(())
+    }
+
+    fn visit_str<E>(self, value: &str) -> core::result::Result<(), E>
+    where
+        E: de::Error,
+    {
+        self.visit_bytes(value.as_bytes())
+    }
+
+    fn visit_bytes<E>(mut self, value: &[u8]) -> core::result::Result<(), E> {
+        self.write_number(value.len());
+        self.0.push(token::BYTES_DELIMITER);
+        self.0.extend_from_slice(value);
+
+        Ok(())
+    }
+
+    fn visit_newtype_struct<D>(self, deserializer: D) -> core::result::Result<(), D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        deserializer.deserialize_any(self)
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> core::result::Result<(), A::Error>
+    where
+        A: de::SeqAccess<'de>,
+    {
+        self.0.push(token::LIST_START);
+        while seq.next_element_seed(Encoder(self.0))?.is_some() {}
+        self.0.push(token::END);
+
+        Ok(())
+    }
+
+    fn visit_map<A>(self, mut map: A) -> core::result::Result<(), A::Error>
+    where
+        A: de::MapAccess<'de>,
+    {
+        self.0.push(token::MAP_START);
+        while map.next_key_seed(Encoder(self.0))?.is_some() {
+            map.next_value_seed(Encoder(self.0))?;
+        }
+        self.0.push(token::END);
+
+        Ok(())
+    }
+}
@@ -1,61 +1,303 @@
 //! Bencode serialization.
 
-use std::{io::Write, str, string::ToString};
+use alloc::{
+    collections::BTreeSet,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::mem;
+#[cfg(feature = "std")]
+use std::io;
 
 use crate::{
-    error::{Error, Result},
-    token,
+    error::{Error, ErrorKind, Result},
+    raw, token,
 };
 
+use lexical::{ToLexical, BUFFER_SIZE};
+use num_traits::float::FloatCore;
 use serde::{ser, Serialize};
 
 /// A structure that serializes Rust values into Bencode.
-pub struct Serializer {
-    data: Vec<u8>,
+///
+/// The serializer writes the output directly into the underlying writer `W`.
+pub struct Serializer<W> {
+    writer: W,
+    raw: bool,
+    options: SerializerOptions,
+}
+
+/// Name of the newtype struct around a `bool`, that the `Serializer` &
+/// `Deserializer` encode as `BoolEncoding::Integer`, whatever their options.
+pub(crate) const INT_BOOL_TOKEN: &str = "$bitrust_bencode::private::IntBool";
+
+/// Encoding of `bool`s, which have no Bencode type of their own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BoolEncoding {
+    /// Byte strings `4:true` & `5:false`.
+    #[default]
+    String,
+    /// Integers `i1e` & `i0e`, as used by the BitTorrent protocols.
+    Integer,
+    /// `bool`s are rejected with `ErrorKind::UnsupportedBool`.
+    Error,
+}
+
+/// Encoding of floats, which have no Bencode type of their own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FloatEncoding {
+    /// Byte strings with the decimal representation, e.g. `3:1.5`.
+    #[default]
+    String,
+    /// Integers with the given number of decimal places, e.g. `1.5` is
+    /// `i1500e` with 3 decimal places. The value is rounded to the nearest
+    /// integer.
+    FixedPoint(u32),
+    /// Floats are rejected with `ErrorKind::UnsupportedFloat`.
+    Error,
+}
+
+/// Options of the `Serializer`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SerializerOptions {
+    /// Writes dictionary keys in the canonical order, see `Serializer::new`
+    /// & `Serializer::unsorted`.
+    pub sort_keys: bool,
+
+    /// Encoding of `bool`s.
+    pub bools: BoolEncoding,
+
+    /// Encoding of `f32` & `f64`.
+    pub floats: FloatEncoding,
+}
+
+impl Default for SerializerOptions {
+    fn default() -> Self {
+        Self {
+            sort_keys: true,
+            bools: BoolEncoding::default(),
+            floats: FloatEncoding::default(),
+        }
+    }
+}
+
+/// A destination of the bytes written by the `Serializer`.
+///
+/// With the `std` feature, it is implemented for every `io::Write`, otherwise
+/// for `Vec<u8>` only.
+pub trait Write {
+    /// Writes all of the bytes.
+    fn write_all(&mut self, bytes: &[u8]) -> Result<()>;
 }
 
-impl Serializer {
-    pub fn new() -> Self {
-        Self { data: Vec::new() }
+#[cfg(feature = "std")]
+impl<W> Write for W
+where
+    W: ?Sized + io::Write,
+{
+    fn write_all(&mut self, bytes: &[u8]) -> Result<()> {
+        io::Write::write_all(self, bytes).map_err(Error::from)
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl Write for Vec<u8> {
+    fn write_all(&mut self, bytes: &[u8]) -> Result<()> {
+        self.extend_from_slice(bytes);
+
+        Ok(())
     }
 }
 
+#[cfg(not(feature = "std"))]
+impl<W> Write for &mut W
+where
+    W: ?Sized + Write,
+{
+    fn write_all(&mut self, bytes: &[u8]) -> Result<()> {
+        (**self).write_all(bytes)
+    }
+}
+
+impl<W> Serializer<W>
+where
+    W: Write,
+{
+    /// Creates a serializer, that writes dictionary keys in the canonical
+    /// order required by the Bencode specification (sorted as raw byte
+    /// strings).
+    ///
+    /// Entries of every dictionary are buffered, until the dictionary ends.
+    pub fn new(writer: W) -> Self {
+        Self::with_options(writer, SerializerOptions::default())
+    }
+
+    /// Creates a serializer, that writes dictionary keys in the order they
+    /// are given by the serialized type.
+    ///
+    /// This avoids buffering of the dictionary entries, but the output is
+    /// only canonical, if the keys are already given in a sorted order.
+    pub fn unsorted(writer: W) -> Self {
+        Self::with_options(
+            writer,
+            SerializerOptions {
+                sort_keys: false,
+                ..SerializerOptions::default()
+            },
+        )
+    }
+
+    /// Creates a serializer with the given options.
+    pub fn with_options(writer: W, options: SerializerOptions) -> Self {
+        Self {
+            writer,
+            raw: false,
+            options,
+        }
+    }
+
+    /// Unwraps the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// Serializes a value as Bencode into the writer.
+pub fn to_writer<W, T>(writer: W, value: &T) -> Result<()>
+where
+    W: Write,
+    T: ?Sized + ser::Serialize,
+{
+    to_writer_with_options(writer, value, SerializerOptions::default())
+}
+
+/// Serializes a value as Bencode into the writer, using the given options.
+pub fn to_writer_with_options<W, T>(writer: W, value: &T, options: SerializerOptions) -> Result<()>
+where
+    W: Write,
+    T: ?Sized + ser::Serialize,
+{
+    let mut ser = Serializer::with_options(writer, options);
+
+    value.serialize(&mut ser)
+}
+
 /// Serializes a value into a `Vec` of bytes containing Bencode value.
 pub fn to_vec<T>(value: &T) -> Result<Vec<u8>>
 where
-    T: ser::Serialize,
+    T: ?Sized + ser::Serialize,
+{
+    to_vec_with_options(value, SerializerOptions::default())
+}
+
+/// Serializes a value into a `Vec` of bytes containing Bencode value, using
+/// the given options.
+pub fn to_vec_with_options<T>(value: &T, options: SerializerOptions) -> Result<Vec<u8>>
+where
+    T: ?Sized + ser::Serialize,
 {
-    let mut ser = Serializer::new();
+    let mut data = Vec::with_capacity(128);
 
-    value.serialize(&mut ser)?;
+    to_writer_with_options(&mut data, value, options)?;
 
-    Ok(ser.data)
+    Ok(data)
 }
 
 /// Serializes a value into a `String` containing Bencode value.
 pub fn to_string<T>(value: &T) -> Result<String>
 where
-    T: ser::Serialize,
+    T: ?Sized + ser::Serialize,
 {
-    let mut ser = Serializer::new();
+    let data = to_vec(value)?;
 
-    value.serialize(&mut ser)?;
-
-    let string = String::from_utf8(ser.data).map_err(|_| Error::InvalidUTF8)?;
+    let string = String::from_utf8(data).map_err(|_| ErrorKind::InvalidUTF8)?;
     Ok(string)
 }
 
-impl Serializer {
+impl<W> Serializer<W>
+where
+    W: Write,
+{
+    /// Creates a serializer with the same settings, that writes into a
+    /// separate buffer.
+    fn buffered(&self) -> Serializer<Vec<u8>> {
+        Serializer {
+            writer: Vec::new(),
+            raw: false,
+            options: self.options,
+        }
+    }
+
+    fn write_bytes(&mut self, value: &[u8]) -> Result<()> {
+        write_integer(&mut self.writer, value.len())?;
+        self.writer.write_all(&[token::BYTES_DELIMITER])?;
+        self.writer.write_all(value)?;
+
+        Ok(())
+    }
+
     fn serialize_integer<T>(&mut self, value: T) -> Result<()>
     where
-        T: ToString,
+        T: ToLexical,
     {
-        self.data.write(&[token::INTEGER_START])?;
-        self.data.write(value.to_string().as_bytes())?;
-        self.data.write(&[token::END])?;
+        self.writer.write_all(&[token::INTEGER_START])?;
+        write_integer(&mut self.writer, value)?;
+        self.writer.write_all(&[token::END])?;
 
         Ok(())
     }
+
+    fn serialize_float<T>(&mut self, value: T) -> Result<()>
+    where
+        T: Into<f64> + ToString,
+    {
+        match self.options.floats {
+            FloatEncoding::String => self.write_bytes(value.to_string().as_bytes()),
+            FloatEncoding::FixedPoint(decimals) => {
+                self.serialize_integer(to_fixed_point(value.into(), decimals)?)
+            }
+            FloatEncoding::Error => Err(ErrorKind::UnsupportedFloat.into()),
+        }
+    }
+}
+
+/// Returns the factor of a fixed-point float with the given number of decimal
+/// places, if it is representable.
+pub(crate) fn fixed_point_scale(decimals: u32) -> Result<f64> {
+    let exponent = i32::try_from(decimals).map_err(|_| ErrorKind::UnsupportedFloat)?;
+    let scale = FloatCore::powi(10f64, exponent);
+
+    if scale.is_finite() {
+        Ok(scale)
+    } else {
+        Err(ErrorKind::UnsupportedFloat.into())
+    }
+}
+
+/// Scales the float into a fixed-point integer with the given number of
+/// decimal places, if it is representable.
+pub(crate) fn to_fixed_point(value: f64, decimals: u32) -> Result<i64> {
+    let scaled = FloatCore::round(value * fixed_point_scale(decimals)?);
+
+    // `i64::MAX` is not exactly representable, its nearest float is already
+    // out of the range.
+    if scaled.is_finite() && scaled >= i64::MIN as f64 && scaled < i64::MAX as f64 {
+        Ok(scaled as i64)
+    } else {
+        Err(ErrorKind::UnsupportedFloat.into())
+    }
+}
+
+/// Formats the integer in decimal directly into the writer, without any
+/// allocation.
+fn write_integer<W, T>(writer: &mut W, value: T) -> Result<()>
+where
+    W: Write,
+    T: ToLexical,
+{
+    let mut buffer = [0u8; BUFFER_SIZE];
+
+    writer.write_all(value.to_lexical(&mut buffer))
 }
 
 macro_rules! fn_serialize_integer {
@@ -66,7 +308,11 @@ macro_rules! fn_serialize_integer {
     };
 }
 
-impl<'a> ser::Serializer for &'a mut Serializer {
+#[allow(clippy::needless_lifetimes)]
+impl<'a, W> ser::Serializer for &'a mut Serializer<W>
+where
+    W: Write,
+{
     type Ok = ();
     type Error = Error;
 
@@ -74,32 +320,32 @@ impl<'a> ser::Serializer for &'a mut Serializer {
     type SerializeTuple = Self;
     type SerializeTupleStruct = Self;
     type SerializeTupleVariant = Self;
-    type SerializeMap = Self;
-    type SerializeStruct = Self;
-    type SerializeStructVariant = Self;
+    type SerializeMap = MapSerializer<'a, W>;
+    type SerializeStruct = MapSerializer<'a, W>;
+    type SerializeStructVariant = MapSerializer<'a, W>;
 
     fn_serialize_integer!(serialize_u8, u8);
     fn_serialize_integer!(serialize_u16, u16);
     fn_serialize_integer!(serialize_u32, u32);
     fn_serialize_integer!(serialize_u64, u64);
-    serde::serde_if_integer128! {
-        fn_serialize_integer!(serialize_u128, u128);
-    }
+    fn_serialize_integer!(serialize_u128, u128);
 
     fn_serialize_integer!(serialize_i8, i8);
     fn_serialize_integer!(serialize_i16, i16);
     fn_serialize_integer!(serialize_i32, i32);
     fn_serialize_integer!(serialize_i64, i64);
-    serde::serde_if_integer128! {
-        fn_serialize_integer!(serialize_i128, i128);
-    }
+    fn_serialize_integer!(serialize_i128, i128);
 
     fn serialize_str(self, value: &str) -> Result<()> {
         self.serialize_bytes(value.as_bytes())
     }
 
     fn serialize_bool(self, value: bool) -> Result<()> {
-        self.serialize_str(if value { "true" } else { "false" })
+        match self.options.bools {
+            BoolEncoding::String => self.serialize_str(if value { "true" } else { "false" }),
+            BoolEncoding::Integer => self.serialize_integer(value as u8),
+            BoolEncoding::Error => Err(ErrorKind::UnsupportedBool.into()),
+        }
     }
 
     fn serialize_char(self, value: char) -> Result<()> {
@@ -107,23 +353,31 @@ impl<'a> ser::Serializer for &'a mut Serializer {
     }
 
     fn serialize_f32(self, value: f32) -> Result<()> {
-        self.serialize_str(&value.to_string())
+        self.serialize_float(value)
     }
 
     fn serialize_f64(self, value: f64) -> Result<()> {
-        self.serialize_str(&value.to_string())
+        self.serialize_float(value)
     }
 
     fn serialize_bytes(self, value: &[u8]) -> Result<()> {
-        self.data.write(value.len().to_string().as_bytes())?;
-        self.data.write(&[token::BYTES_DELIMITER])?;
-        self.data.write(value)?;
+        if self.raw {
+            // Raw values are already encoded, therefore they are written as
+            // they are.
+            self.raw = false;
+            self.writer.write_all(value)?;
 
-        Ok(())
+            return Ok(());
+        }
+
+        self.write_bytes(value)
     }
 
+    /// Bencode has no representation of `None`. Dictionary entries with a
+    /// `None` value are skipped entirely by `MapSerializer`, anywhere else
+    /// it results in an error.
     fn serialize_none(self) -> Result<()> {
-        Ok(())
+        Err(ErrorKind::UnsupportedNone.into())
     }
 
     fn serialize_some<T>(self, value: &T) -> Result<()>
@@ -133,8 +387,10 @@ impl<'a> ser::Serializer for &'a mut Serializer {
         value.serialize(self)
     }
 
+    /// Units are treated the same way as `None`, as they have no Bencode
+    /// representation either.
     fn serialize_unit(self) -> Result<()> {
-        Ok(())
+        Err(ErrorKind::UnsupportedUnit.into())
     }
 
     fn serialize_unit_struct(self, _name: &'static str) -> Result<()> {
@@ -150,10 +406,19 @@ impl<'a> ser::Serializer for &'a mut Serializer {
         self.serialize_str(variant)
     }
 
-    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<()>
+    fn serialize_newtype_struct<T>(self, name: &'static str, value: &T) -> Result<()>
     where
         T: ?Sized + ser::Serialize,
     {
+        if name == INT_BOOL_TOKEN {
+            let bools = mem::replace(&mut self.options.bools, BoolEncoding::Integer);
+            let result = value.serialize(&mut *self);
+            self.options.bools = bools;
+
+            return result;
+        }
+
+        self.raw = name == raw::TOKEN;
         value.serialize(self)
     }
 
@@ -173,12 +438,12 @@ impl<'a> ser::Serializer for &'a mut Serializer {
     where
         T: ?Sized + ser::Serialize,
     {
-        self.data.write(&[token::MAP_START])?;
+        self.writer.write_all(&[token::MAP_START])?;
 
         variant.serialize(&mut *self)?;
         value.serialize(&mut *self)?;
 
-        self.data.write(&[token::END])?;
+        self.writer.write_all(&[token::END])?;
 
         Ok(())
     }
@@ -187,7 +452,7 @@ impl<'a> ser::Serializer for &'a mut Serializer {
     /// method calls. This one is responsible only for serializing the start,
     /// which in Bencode is 'l'.
     fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
-        self.data.write(&[token::LIST_START])?;
+        self.writer.write_all(&[token::LIST_START])?;
 
         Ok(self)
     }
@@ -213,19 +478,19 @@ impl<'a> ser::Serializer for &'a mut Serializer {
         variant: &'static str,
         _len: usize,
     ) -> Result<Self::SerializeTupleVariant> {
-        self.data.write(&[token::MAP_START])?;
+        self.writer.write_all(&[token::MAP_START])?;
 
         variant.serialize(&mut *self)?;
 
-        self.data.write(&[token::LIST_START])?;
+        self.writer.write_all(&[token::LIST_START])?;
 
         Ok(self)
     }
 
     fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap> {
-        self.data.write(&[token::MAP_START])?;
+        self.writer.write_all(&[token::MAP_START])?;
 
-        Ok(self)
+        Ok(MapSerializer::new(self, false))
     }
 
     /// Structs look just like maps in Bencode. In particular, Bencode requires that we
@@ -246,17 +511,21 @@ impl<'a> ser::Serializer for &'a mut Serializer {
         variant: &'static str,
         _len: usize,
     ) -> Result<Self::SerializeStructVariant> {
-        self.data.write(&[token::MAP_START])?;
+        self.writer.write_all(&[token::MAP_START])?;
 
         variant.serialize(&mut *self)?;
 
-        self.data.write(&[token::MAP_START])?;
+        self.writer.write_all(&[token::MAP_START])?;
 
-        Ok(self)
+        Ok(MapSerializer::new(self, true))
     }
 }
 
-impl<'a> ser::SerializeSeq for &'a mut Serializer {
+#[allow(clippy::needless_lifetimes)]
+impl<'a, W> ser::SerializeSeq for &'a mut Serializer<W>
+where
+    W: Write,
+{
     type Ok = ();
     type Error = Error;
 
@@ -268,13 +537,17 @@ impl<'a> ser::SerializeSeq for &'a mut Serializer {
     }
 
     fn end(self) -> Result<()> {
-        self.data.write(&[token::END])?;
+        self.writer.write_all(&[token::END])?;
 
         Ok(())
     }
 }
 
-impl<'a> ser::SerializeTuple for &'a mut Serializer {
+#[allow(clippy::needless_lifetimes)]
+impl<'a, W> ser::SerializeTuple for &'a mut Serializer<W>
+where
+    W: Write,
+{
     type Ok = ();
     type Error = Error;
 
@@ -286,13 +559,17 @@ impl<'a> ser::SerializeTuple for &'a mut Serializer {
     }
 
     fn end(self) -> Result<()> {
-        self.data.write(&[token::END])?;
+        self.writer.write_all(&[token::END])?;
 
         Ok(())
     }
 }
 
-impl<'a> ser::SerializeTupleStruct for &'a mut Serializer {
+#[allow(clippy::needless_lifetimes)]
+impl<'a, W> ser::SerializeTupleStruct for &'a mut Serializer<W>
+where
+    W: Write,
+{
     type Ok = ();
     type Error = Error;
 
@@ -304,13 +581,17 @@ impl<'a> ser::SerializeTupleStruct for &'a mut Serializer {
     }
 
     fn end(self) -> Result<()> {
-        self.data.write(&[token::END])?;
+        self.writer.write_all(&[token::END])?;
 
         Ok(())
     }
 }
 
-impl<'a> ser::SerializeTupleVariant for &'a mut Serializer {
+#[allow(clippy::needless_lifetimes)]
+impl<'a, W> ser::SerializeTupleVariant for &'a mut Serializer<W>
+where
+    W: Write,
+{
     type Ok = ();
     type Error = Error;
 
@@ -323,8 +604,111 @@ impl<'a> ser::SerializeTupleVariant for &'a mut Serializer {
 
     fn end(self) -> Result<()> {
         // Responsible for closing both the dictionary & list.
-        self.data.write(&[token::END])?;
-        self.data.write(&[token::END])?;
+        self.writer.write_all(&[token::END])?;
+        self.writer.write_all(&[token::END])?;
+
+        Ok(())
+    }
+}
+
+/// A structure that serializes Bencode dictionaries (maps, structs & struct
+/// variants).
+///
+/// Unless the serializer is unsorted, the entries are buffered & written in
+/// the canonical order of their keys once the dictionary ends. Otherwise only
+/// the keys are kept, so that duplicates are rejected in both of the modes.
+pub struct MapSerializer<'a, W> {
+    ser: &'a mut Serializer<W>,
+    entries: Option<Vec<(Vec<u8>, Vec<u8>)>>,
+    keys: BTreeSet<Vec<u8>>,
+    key: Option<Vec<u8>>,
+    variant: bool,
+}
+
+impl<'a, W> MapSerializer<'a, W>
+where
+    W: Write,
+{
+    fn new(ser: &'a mut Serializer<W>, variant: bool) -> Self {
+        let entries = if ser.options.sort_keys {
+            Some(Vec::new())
+        } else {
+            None
+        };
+
+        Self {
+            ser,
+            entries,
+            keys: BTreeSet::new(),
+            key: None,
+            variant,
+        }
+    }
+
+    /// The key is held back until its value is known, as the entries with
+    /// a `None` or a unit value are skipped.
+    fn serialize_key<T>(&mut self, key: &T) -> Result<()>
+    where
+        T: ?Sized + ser::Serialize,
+    {
+        self.key = Some(key.serialize(MapKeySerializer)?);
+
+        Ok(())
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + ser::Serialize,
+    {
+        let key = self.key.take().ok_or_else(|| {
+            <Error as ser::Error>::custom("serialize_value called before serialize_key")
+        })?;
+
+        if is_skipped(value) {
+            return Ok(());
+        }
+
+        let entries = match &mut self.entries {
+            Some(entries) => entries,
+            None => {
+                if self.keys.contains(&key) {
+                    return Err(ErrorKind::DuplicateKey.into());
+                }
+
+                self.ser.write_bytes(&key)?;
+                self.keys.insert(key);
+
+                return value.serialize(&mut *self.ser);
+            }
+        };
+
+        let mut ser = self.ser.buffered();
+        value.serialize(&mut ser)?;
+        entries.push((key, ser.writer));
+
+        Ok(())
+    }
+
+    fn end(self) -> Result<()> {
+        if let Some(mut entries) = self.entries {
+            entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+            if entries.windows(2).any(|pair| pair[0].0 == pair[1].0) {
+                return Err(ErrorKind::DuplicateKey.into());
+            }
+
+            for (key, value) in entries {
+                self.ser.write_bytes(&key)?;
+                self.ser.writer.write_all(&value)?;
+            }
+        }
+
+        self.ser.writer.write_all(&[token::END])?;
+
+        if self.variant {
+            // Responsible for closing both of the dictionaries.
+            self.ser.writer.write_all(&[token::END])?;
+        }
 
         Ok(())
     }
@@ -333,43 +717,41 @@ impl<'a> ser::SerializeTupleVariant for &'a mut Serializer {
 /// Some `Serialize` types are not able to hold a key and value in memory at the
 /// same time so `SerializeMap` implementations are required to support
 /// `serialize_key` and `serialize_value` individually.
-impl<'a> ser::SerializeMap for &'a mut Serializer {
+impl<W> ser::SerializeMap for MapSerializer<'_, W>
+where
+    W: Write,
+{
     type Ok = ();
     type Error = Error;
 
     /// The Serde data model allows map keys to be any serializable type. Bencode
-    /// only allows string keys so the implementation below will produce invalid
-    /// Bencode if the key serializes as something other than a string.
-    ///
-    /// A real Bencode serializer would need to validate that map keys are strings.
-    /// This can be done by using a different Serializer to serialize the key
-    /// (instead of `&mut **self`) and having that other serializer only
-    /// implement `serialize_str` and return an error on any other data type.
+    /// only allows string keys, therefore the keys are serialized with
+    /// `MapKeySerializer`, which returns an error on any other data type.
     fn serialize_key<T>(&mut self, key: &T) -> Result<()>
     where
         T: ?Sized + ser::Serialize,
     {
-        // TODO: Make sure that keys are strings.
-        key.serialize(&mut **self)
+        MapSerializer::serialize_key(self, key)
     }
 
     fn serialize_value<T>(&mut self, value: &T) -> Result<()>
     where
         T: ?Sized + ser::Serialize,
     {
-        value.serialize(&mut **self)
+        MapSerializer::serialize_value(self, value)
     }
 
     fn end(self) -> Result<()> {
-        self.data.write(&[token::END])?;
-
-        Ok(())
+        MapSerializer::end(self)
     }
 }
 
 /// Structs are like maps in which the keys are constrained to be compile-time
 /// constant strings.
-impl<'a> ser::SerializeStruct for &'a mut Serializer {
+impl<W> ser::SerializeStruct for MapSerializer<'_, W>
+where
+    W: Write,
+{
     type Ok = ();
     type Error = Error;
 
@@ -377,20 +759,21 @@ impl<'a> ser::SerializeStruct for &'a mut Serializer {
     where
         T: ?Sized + ser::Serialize,
     {
-        key.serialize(&mut **self)?;
-        value.serialize(&mut **self)
+        MapSerializer::serialize_key(self, key)?;
+        MapSerializer::serialize_value(self, value)
     }
 
     fn end(self) -> Result<()> {
-        self.data.write(&[token::END])?;
-
-        Ok(())
+        MapSerializer::end(self)
     }
 }
 
 /// Similar to `SerializeTupleVariant`, here the `end` method is responsible for
-/// closing both of the curly braces opened by `serialize_struct_variant`.
-impl<'a> ser::SerializeStructVariant for &'a mut Serializer {
+/// closing both of the dictionaries opened by `serialize_struct_variant`.
+impl<W> ser::SerializeStructVariant for MapSerializer<'_, W>
+where
+    W: Write,
+{
     type Ok = ();
     type Error = Error;
 
@@ -398,14 +781,355 @@ impl<'a> ser::SerializeStructVariant for &'a mut Serializer {
     where
         T: ?Sized + ser::Serialize,
     {
-        key.serialize(&mut **self)?;
-        value.serialize(&mut **self)
+        MapSerializer::serialize_key(self, key)?;
+        MapSerializer::serialize_value(self, value)
     }
 
     fn end(self) -> Result<()> {
-        self.data.write(&[token::END])?;
-        self.data.write(&[token::END])?;
+        MapSerializer::end(self)
+    }
+}
 
-        Ok(())
+//////////////////////////////////////////////////////
+
+/// Checks whether the value is serialized as `None` or a unit, without
+/// serializing it.
+pub(crate) fn is_skipped<T>(value: &T) -> bool
+where
+    T: ?Sized + ser::Serialize,
+{
+    matches!(value.serialize(NoneProbe), Ok(true))
+}
+
+/// Error of `NoneProbe`, that stops the serialization of compound values.
+#[derive(Debug)]
+struct NotNone;
+
+impl core::fmt::Display for NotNone {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("not none")
+    }
+}
+
+impl core::error::Error for NotNone {}
+
+impl ser::Error for NotNone {
+    fn custom<T>(_msg: T) -> Self
+    where
+        T: core::fmt::Display,
+    {
+        NotNone
+    }
+}
+
+macro_rules! probe_scalar {
+    ($method:ident, $type:ty) => {
+        fn $method(self, _value: $type) -> core::result::Result<bool, NotNone> {
+            Ok(false)
+        }
+    };
+}
+
+/// A serializer, that only finds out whether a value is `None` or a unit.
+///
+/// Newtype structs are looked through, anything else is not skipped.
+struct NoneProbe;
+
+impl ser::Serializer for NoneProbe {
+    type Ok = bool;
+    type Error = NotNone;
+
+    type SerializeSeq = ser::Impossible<bool, NotNone>;
+    type SerializeTuple = ser::Impossible<bool, NotNone>;
+    type SerializeTupleStruct = ser::Impossible<bool, NotNone>;
+    type SerializeTupleVariant = ser::Impossible<bool, NotNone>;
+    type SerializeMap = ser::Impossible<bool, NotNone>;
+    type SerializeStruct = ser::Impossible<bool, NotNone>;
+    type SerializeStructVariant = ser::Impossible<bool, NotNone>;
+
+    probe_scalar!(serialize_bool, bool);
+    probe_scalar!(serialize_i8, i8);
+    probe_scalar!(serialize_i16, i16);
+    probe_scalar!(serialize_i32, i32);
+    probe_scalar!(serialize_i64, i64);
+    probe_scalar!(serialize_u8, u8);
+    probe_scalar!(serialize_u16, u16);
+    probe_scalar!(serialize_u32, u32);
+    probe_scalar!(serialize_u64, u64);
+    probe_scalar!(serialize_f32, f32);
+    probe_scalar!(serialize_f64, f64);
+    probe_scalar!(serialize_char, char);
+    probe_scalar!(serialize_str, &str);
+    probe_scalar!(serialize_bytes, &[u8]);
+
+    fn serialize_none(self) -> core::result::Result<bool, NotNone> {
+        Ok(true)
+    }
+
+    fn serialize_some<T>(self, _value: &T) -> core::result::Result<bool, NotNone>
+    where
+        T: ?Sized + ser::Serialize,
+    {
+        Ok(false)
+    }
+
+    fn serialize_unit(self) -> core::result::Result<bool, NotNone> {
+        Ok(true)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> core::result::Result<bool, NotNone> {
+        Ok(true)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+    ) -> core::result::Result<bool, NotNone> {
+        Ok(false)
+    }
+
+    fn serialize_newtype_struct<T>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> core::result::Result<bool, NotNone>
+    where
+        T: ?Sized + ser::Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> core::result::Result<bool, NotNone>
+    where
+        T: ?Sized + ser::Serialize,
+    {
+        Ok(false)
+    }
+
+    fn serialize_seq(
+        self,
+        _len: Option<usize>,
+    ) -> core::result::Result<Self::SerializeSeq, NotNone> {
+        Err(NotNone)
+    }
+
+    fn serialize_tuple(self, _len: usize) -> core::result::Result<Self::SerializeTuple, NotNone> {
+        Err(NotNone)
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> core::result::Result<Self::SerializeTupleStruct, NotNone> {
+        Err(NotNone)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> core::result::Result<Self::SerializeTupleVariant, NotNone> {
+        Err(NotNone)
+    }
+
+    fn serialize_map(
+        self,
+        _len: Option<usize>,
+    ) -> core::result::Result<Self::SerializeMap, NotNone> {
+        Err(NotNone)
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> core::result::Result<Self::SerializeStruct, NotNone> {
+        Err(NotNone)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> core::result::Result<Self::SerializeStructVariant, NotNone> {
+        Err(NotNone)
+    }
+}
+
+//////////////////////////////////////////////////////
+
+/// A serializer of dictionary keys, that produces the raw bytes of a key.
+///
+/// Strings & bytes are accepted as they are. Integers & chars are converted
+/// into their string representation, as many BitTorrent clients do for keys
+/// such as piece indices. Any other data type results in an error.
+pub(crate) struct MapKeySerializer;
+
+fn key_must_be_a_string() -> Error {
+    ErrorKind::KeyMustBeAString.into()
+}
+
+macro_rules! fn_serialize_integer_key {
+    ($method:ident, $type:ty) => {
+        fn $method(self, value: $type) -> Result<Vec<u8>> {
+            let mut key = Vec::new();
+            write_integer(&mut key, value)?;
+
+            Ok(key)
+        }
+    };
+}
+
+impl ser::Serializer for MapKeySerializer {
+    type Ok = Vec<u8>;
+    type Error = Error;
+
+    type SerializeSeq = ser::Impossible<Vec<u8>, Error>;
+    type SerializeTuple = ser::Impossible<Vec<u8>, Error>;
+    type SerializeTupleStruct = ser::Impossible<Vec<u8>, Error>;
+    type SerializeTupleVariant = ser::Impossible<Vec<u8>, Error>;
+    type SerializeMap = ser::Impossible<Vec<u8>, Error>;
+    type SerializeStruct = ser::Impossible<Vec<u8>, Error>;
+    type SerializeStructVariant = ser::Impossible<Vec<u8>, Error>;
+
+    fn_serialize_integer_key!(serialize_u8, u8);
+    fn_serialize_integer_key!(serialize_u16, u16);
+    fn_serialize_integer_key!(serialize_u32, u32);
+    fn_serialize_integer_key!(serialize_u64, u64);
+    fn_serialize_integer_key!(serialize_u128, u128);
+
+    fn_serialize_integer_key!(serialize_i8, i8);
+    fn_serialize_integer_key!(serialize_i16, i16);
+    fn_serialize_integer_key!(serialize_i32, i32);
+    fn_serialize_integer_key!(serialize_i64, i64);
+    fn_serialize_integer_key!(serialize_i128, i128);
+
+    fn serialize_str(self, value: &str) -> Result<Vec<u8>> {
+        Ok(value.as_bytes().to_vec())
+    }
+
+    fn serialize_bytes(self, value: &[u8]) -> Result<Vec<u8>> {
+        Ok(value.to_vec())
+    }
+
+    fn serialize_char(self, value: char) -> Result<Vec<u8>> {
+        Ok(value.to_string().into_bytes())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Vec<u8>> {
+        self.serialize_str(variant)
+    }
+
+    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<Vec<u8>>
+    where
+        T: ?Sized + ser::Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_bool(self, _value: bool) -> Result<Vec<u8>> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_f32(self, _value: f32) -> Result<Vec<u8>> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_f64(self, _value: f64) -> Result<Vec<u8>> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_none(self) -> Result<Vec<u8>> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_some<T>(self, _value: &T) -> Result<Vec<u8>>
+    where
+        T: ?Sized + ser::Serialize,
+    {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_unit(self) -> Result<Vec<u8>> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Vec<u8>> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<Vec<u8>>
+    where
+        T: ?Sized + ser::Serialize,
+    {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeStruct> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant> {
+        Err(key_must_be_a_string())
     }
 }
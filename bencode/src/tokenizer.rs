@@ -0,0 +1,206 @@
+//! Low-level, pull-based tokenizer of Bencode.
+
+use alloc::vec::Vec;
+
+use crate::{
+    de::{
+        consume_bytes, consume_bytes_delimiter, consume_end, consume_integer_start,
+        consume_signed_number, consume_unsigned_number,
+    },
+    error::{ErrorKind, Result},
+    token,
+};
+
+/// A single token of Bencode.
+///
+/// Integers are split into `IntStart`, `Integer` & `End`, the same way lists
+/// & dictionaries are closed by `End`. Keys & values of a dictionary are
+/// returned one after another.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event<'a> {
+    /// Start of an integer `i`.
+    IntStart,
+    /// Number of an integer.
+    Integer(i64),
+    /// Byte string `<length>:<bytes>`, borrowed from the input.
+    Bytes(&'a [u8]),
+    /// Start of a list `l`.
+    ListStart,
+    /// Start of a dictionary `d`.
+    DictStart,
+    /// End of an integer, list or dictionary `e`.
+    End,
+}
+
+/// What the tokenizer expects to read next.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Expect {
+    Value,
+    Key,
+    Number,
+    IntegerEnd,
+    Nothing,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Container {
+    List,
+    Dict,
+}
+
+/// An iterator over the events of a single Bencode value, together with the
+/// byte offsets at which they start.
+///
+/// The tokenizer does not materialise any values & borrows the byte strings
+/// from the input, which makes it suitable for scanners, such as locating
+/// the `info` dictionary of a torrent.
+///
+/// The structure of the input is validated, e.g. that dictionary keys are
+/// byte strings & that there are no trailing characters. Once an error is
+/// returned, the iteration stops.
+pub struct Tokenizer<'a> {
+    input: &'a [u8],
+    data: &'a [u8],
+    stack: Vec<Container>,
+    expect: Expect,
+}
+
+impl<'a> Tokenizer<'a> {
+    /// Creates a tokenizer of a byte slice containing a single Bencode value.
+    pub fn new(data: &'a [u8]) -> Self {
+        Self {
+            input: data,
+            data,
+            stack: Vec::new(),
+            expect: Expect::Value,
+        }
+    }
+
+    /// Returns the byte offset of the next event in the input.
+    pub fn offset(&self) -> usize {
+        self.input.len() - self.data.len()
+    }
+
+    /// Returns the number of lists & dictionaries, that are currently open.
+    pub fn depth(&self) -> usize {
+        self.stack.len()
+    }
+
+    /// Returns the remaining, not yet tokenized, part of the input.
+    pub fn remaining(&self) -> &'a [u8] {
+        self.data
+    }
+
+    fn finish_value(&mut self) {
+        self.expect = match self.stack.last() {
+            None => Expect::Nothing,
+            Some(Container::List) => Expect::Value,
+            Some(Container::Dict) => Expect::Key,
+        };
+    }
+
+    fn parse_bytes(&mut self) -> Result<&'a [u8]> {
+        let (data, count) = consume_unsigned_number::<usize>(self.data)?;
+        let data = consume_bytes_delimiter(data)?;
+        let (data, bytes) = consume_bytes(data, count)?;
+        self.data = data;
+
+        Ok(bytes)
+    }
+
+    fn parse_event(&mut self) -> Result<Event<'a>> {
+        match self.expect {
+            Expect::Number => {
+                let (data, number) = consume_signed_number::<i64>(self.data)?;
+                self.data = data;
+                self.expect = Expect::IntegerEnd;
+
+                Ok(Event::Integer(number))
+            }
+            Expect::IntegerEnd => {
+                self.data = consume_end(self.data, ErrorKind::ExpectedIntegerEnd)?;
+                self.finish_value();
+
+                Ok(Event::End)
+            }
+            Expect::Key => match self.data.first() {
+                None => Err(ErrorKind::EOF.into()),
+                Some(&token::END) => {
+                    self.data = &self.data[1..];
+                    self.stack.pop();
+                    self.finish_value();
+
+                    Ok(Event::End)
+                }
+                Some(b'0'..=b'9') => {
+                    let key = self.parse_bytes()?;
+                    self.expect = Expect::Value;
+
+                    Ok(Event::Bytes(key))
+                }
+                Some(_) => Err(ErrorKind::ExpectedDictionaryKeyString.into()),
+            },
+            Expect::Value => match self.data.first() {
+                None => Err(ErrorKind::EOF.into()),
+                Some(&token::INTEGER_START) => {
+                    self.data = consume_integer_start(self.data)?;
+                    self.expect = Expect::Number;
+
+                    Ok(Event::IntStart)
+                }
+                Some(&token::LIST_START) => {
+                    self.data = &self.data[1..];
+                    self.stack.push(Container::List);
+                    self.expect = Expect::Value;
+
+                    Ok(Event::ListStart)
+                }
+                Some(&token::MAP_START) => {
+                    self.data = &self.data[1..];
+                    self.stack.push(Container::Dict);
+                    self.expect = Expect::Key;
+
+                    Ok(Event::DictStart)
+                }
+                Some(&token::END) if self.stack.last() == Some(&Container::List) => {
+                    self.data = &self.data[1..];
+                    self.stack.pop();
+                    self.finish_value();
+
+                    Ok(Event::End)
+                }
+                Some(b'0'..=b'9') => {
+                    let bytes = self.parse_bytes()?;
+                    self.finish_value();
+
+                    Ok(Event::Bytes(bytes))
+                }
+                Some(_) => Err(ErrorKind::UnknownType.into()),
+            },
+            Expect::Nothing => Err(ErrorKind::TrailingCharacters.into()),
+        }
+    }
+}
+
+impl<'a> Iterator for Tokenizer<'a> {
+    type Item = Result<(usize, Event<'a>)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.expect == Expect::Nothing && self.data.is_empty() {
+            return None;
+        }
+
+        let offset = self.offset();
+
+        match self.parse_event() {
+            Ok(event) => Some(Ok((offset, event))),
+            Err(e) => {
+                // Stop the iteration after the first error.
+                self.data = &[];
+                self.expect = Expect::Nothing;
+
+                Some(Err(e.locate(offset, || None)))
+            }
+        }
+    }
+}
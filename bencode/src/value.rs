@@ -0,0 +1,1575 @@
+//! Dynamically typed Bencode value.
+
+use alloc::{
+    collections::{btree_map, BTreeMap},
+    string::{String, ToString},
+    vec::{self, Vec},
+};
+use core::{fmt, ops, str};
+
+use crate::{
+    de::{
+        consume_signed_number, consume_unsigned_number, from_slice, integer_key, visit_integer,
+        DeserializerOptions,
+    },
+    error::{Error, ErrorKind, Result},
+    raw,
+    ser::{
+        fixed_point_scale, is_skipped, to_fixed_point, to_vec, BoolEncoding, FloatEncoding,
+        MapKeySerializer, SerializerOptions, INT_BOOL_TOKEN,
+    },
+};
+
+use num_traits::float::FloatCore;
+use serde::{
+    de::{self, IntoDeserializer},
+    ser,
+};
+
+/// Represents any valid Bencode value.
+///
+/// Dictionary keys are kept as raw byte strings & are ordered the same way
+/// as the Bencode specification requires them to be (lexicographically by
+/// their bytes).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Value {
+    /// Represents a Bencode integer, `i<number>e`.
+    ///
+    /// Bencode integers have no fixed size, `i128` holds both the whole
+    /// `i64` & `u64` ranges.
+    Integer(i128),
+
+    /// Represents a Bencode byte string, `<length>:<bytes>`.
+    Bytes(Vec<u8>),
+
+    /// Represents a Bencode list, `l<values>e`.
+    List(Vec<Value>),
+
+    /// Represents a Bencode dictionary, `d<key><value>...e`.
+    Dict(BTreeMap<Vec<u8>, Value>),
+}
+
+impl Value {
+    /// Returns an element of a list or a value of a dictionary, depending on
+    /// the type of the index.
+    ///
+    /// Returns `None`, if the value is not of the indexed type, or if the
+    /// element or key does not exist.
+    pub fn get<I: Index>(&self, index: I) -> Option<&Value> {
+        index.index_into(self)
+    }
+
+    /// Mutable version of `get`.
+    pub fn get_mut<I: Index>(&mut self, index: I) -> Option<&mut Value> {
+        index.index_into_mut(self)
+    }
+
+    pub fn is_int(&self) -> bool {
+        matches!(self, Value::Integer(_))
+    }
+
+    pub fn is_bytes(&self) -> bool {
+        self.as_bytes().is_some()
+    }
+
+    pub fn is_list(&self) -> bool {
+        self.as_list().is_some()
+    }
+
+    pub fn is_dict(&self) -> bool {
+        self.as_dict().is_some()
+    }
+
+    /// Returns the integer, if the value is an integer, that fits into
+    /// `i64`.
+    pub fn as_int(&self) -> Option<i64> {
+        self.as_i128().and_then(|value| i64::try_from(value).ok())
+    }
+
+    /// Returns the integer, if the value is an integer, that fits into
+    /// `u64`.
+    pub fn as_u64(&self) -> Option<u64> {
+        self.as_i128().and_then(|value| u64::try_from(value).ok())
+    }
+
+    /// Returns the integer, if the value is an integer.
+    pub fn as_i128(&self) -> Option<i128> {
+        match self {
+            Value::Integer(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Returns the raw bytes, if the value is a byte string.
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            Value::Bytes(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the string, if the value is a byte string containing valid
+    /// UTF-8.
+    pub fn as_str(&self) -> Option<&str> {
+        self.as_bytes().and_then(|bytes| str::from_utf8(bytes).ok())
+    }
+
+    /// Returns the elements, if the value is a list.
+    pub fn as_list(&self) -> Option<&Vec<Value>> {
+        match self {
+            Value::List(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Mutable version of `as_list`.
+    pub fn as_list_mut(&mut self) -> Option<&mut Vec<Value>> {
+        match self {
+            Value::List(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the entries, if the value is a dictionary.
+    pub fn as_dict(&self) -> Option<&BTreeMap<Vec<u8>, Value>> {
+        match self {
+            Value::Dict(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Mutable version of `as_dict`.
+    pub fn as_dict_mut(&mut self) -> Option<&mut BTreeMap<Vec<u8>, Value>> {
+        match self {
+            Value::Dict(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+/// Converts a value of type `T` into a `Value`.
+///
+/// The conversion follows the same rules as `to_vec` does with the default
+/// options, without encoding the value. The keys of a dictionary are sorted
+/// as well, since a `Value` keeps its dictionaries in a `BTreeMap`.
+pub fn to_value<T>(value: &T) -> Result<Value>
+where
+    T: ?Sized + ser::Serialize,
+{
+    value.serialize(ValueSerializer::new(SerializerOptions::default()))
+}
+
+/// Converts a `Value` into a type `T`.
+///
+/// The conversion follows the same rules as `from_slice` does with the
+/// default options, without decoding the value. As the value is already in
+/// memory, its nesting depth is not limited.
+pub fn from_value<T>(value: Value) -> Result<T>
+where
+    T: de::DeserializeOwned,
+{
+    T::deserialize(ValueDeserializer::new(
+        value,
+        DeserializerOptions::default(),
+    ))
+}
+
+//////////////////////////////////////////////////////
+
+mod private {
+    use alloc::{string::String, vec::Vec};
+
+    pub trait Sealed {}
+    impl Sealed for usize {}
+    impl Sealed for str {}
+    impl Sealed for [u8] {}
+    impl Sealed for String {}
+    impl Sealed for Vec<u8> {}
+    impl<const N: usize> Sealed for [u8; N] {}
+    impl<T> Sealed for &T where T: ?Sized + Sealed {}
+}
+
+/// A type that can be used to index into a `Value`.
+///
+/// Integers index into lists, while strings & byte strings index into
+/// dictionaries. This trait is sealed & cannot be implemented outside of
+/// this crate.
+pub trait Index: private::Sealed {
+    #[doc(hidden)]
+    fn index_into<'v>(&self, value: &'v Value) -> Option<&'v Value>;
+
+    #[doc(hidden)]
+    fn index_into_mut<'v>(&self, value: &'v mut Value) -> Option<&'v mut Value>;
+}
+
+impl Index for usize {
+    fn index_into<'v>(&self, value: &'v Value) -> Option<&'v Value> {
+        value.as_list().and_then(|list| list.get(*self))
+    }
+
+    fn index_into_mut<'v>(&self, value: &'v mut Value) -> Option<&'v mut Value> {
+        value.as_list_mut().and_then(|list| list.get_mut(*self))
+    }
+}
+
+impl Index for [u8] {
+    fn index_into<'v>(&self, value: &'v Value) -> Option<&'v Value> {
+        value.as_dict().and_then(|dict| dict.get(self))
+    }
+
+    fn index_into_mut<'v>(&self, value: &'v mut Value) -> Option<&'v mut Value> {
+        value.as_dict_mut().and_then(|dict| dict.get_mut(self))
+    }
+}
+
+impl Index for str {
+    fn index_into<'v>(&self, value: &'v Value) -> Option<&'v Value> {
+        self.as_bytes().index_into(value)
+    }
+
+    fn index_into_mut<'v>(&self, value: &'v mut Value) -> Option<&'v mut Value> {
+        self.as_bytes().index_into_mut(value)
+    }
+}
+
+impl Index for String {
+    fn index_into<'v>(&self, value: &'v Value) -> Option<&'v Value> {
+        self.as_bytes().index_into(value)
+    }
+
+    fn index_into_mut<'v>(&self, value: &'v mut Value) -> Option<&'v mut Value> {
+        self.as_bytes().index_into_mut(value)
+    }
+}
+
+impl Index for Vec<u8> {
+    fn index_into<'v>(&self, value: &'v Value) -> Option<&'v Value> {
+        self.as_slice().index_into(value)
+    }
+
+    fn index_into_mut<'v>(&self, value: &'v mut Value) -> Option<&'v mut Value> {
+        self.as_slice().index_into_mut(value)
+    }
+}
+
+impl<T> Index for &T
+where
+    T: ?Sized + Index,
+{
+    fn index_into<'v>(&self, value: &'v Value) -> Option<&'v Value> {
+        (**self).index_into(value)
+    }
+
+    fn index_into_mut<'v>(&self, value: &'v mut Value) -> Option<&'v mut Value> {
+        (**self).index_into_mut(value)
+    }
+}
+
+/// A type that can be used as a key of a dictionary built by the `bencode!`
+/// macro.
+///
+/// Only strings & byte strings are keys, therefore any other key is rejected
+/// at compile time. This trait is sealed & cannot be implemented outside of
+/// this crate.
+pub trait DictKey: private::Sealed {
+    #[doc(hidden)]
+    fn to_key(&self) -> Vec<u8>;
+}
+
+impl DictKey for [u8] {
+    fn to_key(&self) -> Vec<u8> {
+        self.to_vec()
+    }
+}
+
+impl DictKey for str {
+    fn to_key(&self) -> Vec<u8> {
+        self.as_bytes().to_vec()
+    }
+}
+
+impl DictKey for String {
+    fn to_key(&self) -> Vec<u8> {
+        self.as_bytes().to_vec()
+    }
+}
+
+impl DictKey for Vec<u8> {
+    fn to_key(&self) -> Vec<u8> {
+        self.clone()
+    }
+}
+
+impl<const N: usize> DictKey for [u8; N] {
+    fn to_key(&self) -> Vec<u8> {
+        self.to_vec()
+    }
+}
+
+impl<T> DictKey for &T
+where
+    T: ?Sized + DictKey,
+{
+    fn to_key(&self) -> Vec<u8> {
+        (**self).to_key()
+    }
+}
+
+/// Indexing into a `Value` panics, if the value is not of the indexed type,
+/// or if the element or key does not exist. Use `Value::get` for a
+/// non-panicking alternative.
+impl<I> ops::Index<I> for Value
+where
+    I: Index + fmt::Debug,
+{
+    type Output = Value;
+
+    fn index(&self, index: I) -> &Value {
+        match index.index_into(self) {
+            Some(value) => value,
+            None => panic!("cannot index into bencode value with {:?}", index),
+        }
+    }
+}
+
+impl<I> ops::IndexMut<I> for Value
+where
+    I: Index + fmt::Debug,
+{
+    fn index_mut(&mut self, index: I) -> &mut Value {
+        match index.index_into_mut(self) {
+            Some(value) => value,
+            None => panic!("cannot index into bencode value with {:?}", index),
+        }
+    }
+}
+
+//////////////////////////////////////////////////////
+
+macro_rules! impl_from_integer {
+    ($($type:ty),*) => {
+        $(
+            impl From<$type> for Value {
+                fn from(value: $type) -> Self {
+                    Value::Integer(value as i128)
+                }
+            }
+        )*
+    };
+}
+
+impl_from_integer!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize, i128);
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Value::Bytes(value.as_bytes().to_vec())
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Value::Bytes(value.into_bytes())
+    }
+}
+
+impl From<&[u8]> for Value {
+    fn from(value: &[u8]) -> Self {
+        Value::Bytes(value.to_vec())
+    }
+}
+
+impl<const N: usize> From<&[u8; N]> for Value {
+    fn from(value: &[u8; N]) -> Self {
+        Value::Bytes(value.to_vec())
+    }
+}
+
+impl<const N: usize> From<[u8; N]> for Value {
+    fn from(value: [u8; N]) -> Self {
+        Value::Bytes(value.to_vec())
+    }
+}
+
+impl From<Vec<u8>> for Value {
+    fn from(value: Vec<u8>) -> Self {
+        Value::Bytes(value)
+    }
+}
+
+impl From<Vec<Value>> for Value {
+    fn from(value: Vec<Value>) -> Self {
+        Value::List(value)
+    }
+}
+
+impl From<BTreeMap<Vec<u8>, Value>> for Value {
+    fn from(value: BTreeMap<Vec<u8>, Value>) -> Self {
+        Value::Dict(value)
+    }
+}
+
+//////////////////////////////////////////////////////
+
+/// Byte string key, that is serialized as raw bytes instead of a sequence.
+pub(crate) struct Key<'a>(pub(crate) &'a [u8]);
+
+impl ser::Serialize for Key<'_> {
+    fn serialize<S>(&self, serializer: S) -> core::result::Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        serializer.serialize_bytes(self.0)
+    }
+}
+
+impl ser::Serialize for Value {
+    fn serialize<S>(&self, serializer: S) -> core::result::Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        match self {
+            Value::Integer(value) => {
+                // The smallest type is used, as not every serializer supports
+                // 128-bit integers.
+                if let Ok(value) = i64::try_from(*value) {
+                    serializer.serialize_i64(value)
+                } else if let Ok(value) = u64::try_from(*value) {
+                    serializer.serialize_u64(value)
+                } else {
+                    serializer.serialize_i128(*value)
+                }
+            }
+            Value::Bytes(value) => serializer.serialize_bytes(value),
+            Value::List(value) => serializer.collect_seq(value),
+            Value::Dict(value) => serializer.collect_map(value.iter().map(|(k, v)| (Key(k), v))),
+        }
+    }
+}
+
+//////////////////////////////////////////////////////
+
+/// Byte string key, that accepts both strings & bytes during deserialization.
+pub(crate) struct KeyBuf(pub(crate) Vec<u8>);
+
+impl<'de> de::Deserialize<'de> for KeyBuf {
+    fn deserialize<D>(deserializer: D) -> core::result::Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        struct KeyVisitor;
+
+        impl de::Visitor<'_> for KeyVisitor {
+            type Value = KeyBuf;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a byte string key")
+            }
+
+            fn visit_str<E>(self, value: &str) -> core::result::Result<KeyBuf, E> {
+                Ok(KeyBuf(value.as_bytes().to_vec()))
+            }
+
+            fn visit_string<E>(self, value: String) -> core::result::Result<KeyBuf, E> {
+                Ok(KeyBuf(value.into_bytes()))
+            }
+
+            fn visit_bytes<E>(self, value: &[u8]) -> core::result::Result<KeyBuf, E> {
+                Ok(KeyBuf(value.to_vec()))
+            }
+
+            fn visit_byte_buf<E>(self, value: Vec<u8>) -> core::result::Result<KeyBuf, E> {
+                Ok(KeyBuf(value))
+            }
+        }
+
+        deserializer.deserialize_bytes(KeyVisitor)
+    }
+}
+
+impl<'de> de::Deserialize<'de> for Value {
+    fn deserialize<D>(deserializer: D) -> core::result::Result<Value, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        struct ValueVisitor;
+
+        impl<'de> de::Visitor<'de> for ValueVisitor {
+            type Value = Value;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("any valid bencode value")
+            }
+
+            fn visit_i64<E>(self, value: i64) -> core::result::Result<Value, E> {
+                Ok(Value::from(value))
+            }
+
+            fn visit_u64<E>(self, value: u64) -> core::result::Result<Value, E> {
+                Ok(Value::from(value))
+            }
+
+            fn visit_i128<E>(self, value: i128) -> core::result::Result<Value, E> {
+                Ok(Value::Integer(value))
+            }
+
+            fn visit_u128<E>(self, value: u128) -> core::result::Result<Value, E>
+            where
+                E: de::Error,
+            {
+                i128::try_from(value)
+                    .map(Value::Integer)
+                    .map_err(|_| E::custom(ErrorKind::IntegerOverflow))
+            }
+
+            fn visit_str<E>(self, value: &str) -> core::result::Result<Value, E> {
+                Ok(Value::from(value))
+            }
+
+            fn visit_string<E>(self, value: String) -> core::result::Result<Value, E> {
+                Ok(Value::from(value))
+            }
+
+            fn visit_bytes<E>(self, value: &[u8]) -> core::result::Result<Value, E> {
+                Ok(Value::from(value))
+            }
+
+            fn visit_byte_buf<E>(self, value: Vec<u8>) -> core::result::Result<Value, E> {
+                Ok(Value::Bytes(value))
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> core::result::Result<Value, A::Error>
+            where
+                A: de::SeqAccess<'de>,
+            {
+                let mut list = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+
+                while let Some(value) = seq.next_element()? {
+                    list.push(value);
+                }
+
+                Ok(Value::List(list))
+            }
+
+            fn visit_map<A>(self, mut map: A) -> core::result::Result<Value, A::Error>
+            where
+                A: de::MapAccess<'de>,
+            {
+                let mut dict = BTreeMap::new();
+
+                while let Some((KeyBuf(key), value)) = map.next_entry()? {
+                    dict.insert(key, value);
+                }
+
+                Ok(Value::Dict(dict))
+            }
+        }
+
+        deserializer.deserialize_any(ValueVisitor)
+    }
+}
+
+//////////////////////////////////////////////////////
+
+/// A serializer of Rust values into a `Value`, the counterpart of the
+/// `Serializer`, that builds the value in memory.
+struct ValueSerializer {
+    options: SerializerOptions,
+}
+
+impl ValueSerializer {
+    fn new(options: SerializerOptions) -> Self {
+        Self { options }
+    }
+
+    fn serialize_float<T>(self, value: T) -> Result<Value>
+    where
+        T: Into<f64> + ToString,
+    {
+        match self.options.floats {
+            FloatEncoding::String => Ok(Value::from(value.to_string())),
+            FloatEncoding::FixedPoint(decimals) => {
+                Ok(Value::from(to_fixed_point(value.into(), decimals)?))
+            }
+            FloatEncoding::Error => Err(ErrorKind::UnsupportedFloat.into()),
+        }
+    }
+}
+
+macro_rules! fn_serialize_integer {
+    ($method:ident, $type:ty) => {
+        fn $method(self, value: $type) -> Result<Value> {
+            Ok(Value::from(value))
+        }
+    };
+}
+
+impl ser::Serializer for ValueSerializer {
+    type Ok = Value;
+    type Error = Error;
+
+    type SerializeSeq = ListSerializer;
+    type SerializeTuple = ListSerializer;
+    type SerializeTupleStruct = ListSerializer;
+    type SerializeTupleVariant = ListSerializer;
+    type SerializeMap = DictSerializer;
+    type SerializeStruct = DictSerializer;
+    type SerializeStructVariant = DictSerializer;
+
+    fn_serialize_integer!(serialize_u8, u8);
+    fn_serialize_integer!(serialize_u16, u16);
+    fn_serialize_integer!(serialize_u32, u32);
+    fn_serialize_integer!(serialize_u64, u64);
+
+    fn_serialize_integer!(serialize_i8, i8);
+    fn_serialize_integer!(serialize_i16, i16);
+    fn_serialize_integer!(serialize_i32, i32);
+    fn_serialize_integer!(serialize_i64, i64);
+    fn_serialize_integer!(serialize_i128, i128);
+
+    fn serialize_u128(self, value: u128) -> Result<Value> {
+        i128::try_from(value)
+            .map(Value::Integer)
+            .map_err(|_| ErrorKind::IntegerOverflow.into())
+    }
+
+    fn serialize_str(self, value: &str) -> Result<Value> {
+        Ok(Value::from(value))
+    }
+
+    fn serialize_bool(self, value: bool) -> Result<Value> {
+        match self.options.bools {
+            BoolEncoding::String => self.serialize_str(if value { "true" } else { "false" }),
+            BoolEncoding::Integer => Ok(Value::from(value as u8)),
+            BoolEncoding::Error => Err(ErrorKind::UnsupportedBool.into()),
+        }
+    }
+
+    fn serialize_char(self, value: char) -> Result<Value> {
+        Ok(Value::from(value.to_string()))
+    }
+
+    fn serialize_f32(self, value: f32) -> Result<Value> {
+        self.serialize_float(value)
+    }
+
+    fn serialize_f64(self, value: f64) -> Result<Value> {
+        self.serialize_float(value)
+    }
+
+    fn serialize_bytes(self, value: &[u8]) -> Result<Value> {
+        Ok(Value::from(value))
+    }
+
+    fn serialize_none(self) -> Result<Value> {
+        Err(ErrorKind::UnsupportedNone.into())
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<Value>
+    where
+        T: ?Sized + ser::Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Value> {
+        Err(ErrorKind::UnsupportedUnit.into())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Value> {
+        self.serialize_unit()
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Value> {
+        self.serialize_str(variant)
+    }
+
+    /// Raw values serialize their encoded bytes, which are decoded back into
+    /// a value.
+    fn serialize_newtype_struct<T>(self, name: &'static str, value: &T) -> Result<Value>
+    where
+        T: ?Sized + ser::Serialize,
+    {
+        if name == INT_BOOL_TOKEN {
+            return value.serialize(ValueSerializer::new(SerializerOptions {
+                bools: BoolEncoding::Integer,
+                ..self.options
+            }));
+        }
+
+        match value.serialize(self)? {
+            Value::Bytes(bytes) if name == raw::TOKEN => from_slice(&bytes),
+            value => Ok(value),
+        }
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Value>
+    where
+        T: ?Sized + ser::Serialize,
+    {
+        Ok(tag_variant(Some(variant), value.serialize(self)?))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq> {
+        Ok(ListSerializer::new(self.options, len, None))
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleStruct> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleVariant> {
+        Ok(ListSerializer::new(self.options, Some(len), Some(variant)))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap> {
+        Ok(DictSerializer::new(self.options, None))
+    }
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<Self::SerializeStruct> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant> {
+        Ok(DictSerializer::new(self.options, Some(variant)))
+    }
+}
+
+/// Wraps the value of an enum variant into a dictionary with a single key,
+/// the externally tagged representation.
+fn tag_variant(variant: Option<&'static str>, value: Value) -> Value {
+    match variant {
+        Some(variant) => Value::Dict(BTreeMap::from([(variant.as_bytes().to_vec(), value)])),
+        None => value,
+    }
+}
+
+/// A serializer of lists (sequences, tuples, tuple structs & tuple
+/// variants) into a `Value`.
+struct ListSerializer {
+    options: SerializerOptions,
+    list: Vec<Value>,
+    variant: Option<&'static str>,
+}
+
+impl ListSerializer {
+    fn new(options: SerializerOptions, len: Option<usize>, variant: Option<&'static str>) -> Self {
+        Self {
+            options,
+            list: Vec::with_capacity(len.unwrap_or(0)),
+            variant,
+        }
+    }
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + ser::Serialize,
+    {
+        self.list
+            .push(value.serialize(ValueSerializer::new(self.options))?);
+
+        Ok(())
+    }
+
+    fn end(self) -> Result<Value> {
+        Ok(tag_variant(self.variant, Value::List(self.list)))
+    }
+}
+
+impl ser::SerializeSeq for ListSerializer {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + ser::Serialize,
+    {
+        ListSerializer::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Value> {
+        ListSerializer::end(self)
+    }
+}
+
+impl ser::SerializeTuple for ListSerializer {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + ser::Serialize,
+    {
+        ListSerializer::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Value> {
+        ListSerializer::end(self)
+    }
+}
+
+impl ser::SerializeTupleStruct for ListSerializer {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + ser::Serialize,
+    {
+        ListSerializer::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Value> {
+        ListSerializer::end(self)
+    }
+}
+
+impl ser::SerializeTupleVariant for ListSerializer {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + ser::Serialize,
+    {
+        ListSerializer::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Value> {
+        ListSerializer::end(self)
+    }
+}
+
+/// A serializer of dictionaries (maps, structs & struct variants) into a
+/// `Value`.
+///
+/// The keys are serialized the same way as by the `Serializer`, entries with
+/// a `None` or a unit value are skipped & duplicate keys are rejected.
+struct DictSerializer {
+    options: SerializerOptions,
+    dict: BTreeMap<Vec<u8>, Value>,
+    key: Option<Vec<u8>>,
+    variant: Option<&'static str>,
+}
+
+impl DictSerializer {
+    fn new(options: SerializerOptions, variant: Option<&'static str>) -> Self {
+        Self {
+            options,
+            dict: BTreeMap::new(),
+            key: None,
+            variant,
+        }
+    }
+
+    fn serialize_key<T>(&mut self, key: &T) -> Result<()>
+    where
+        T: ?Sized + ser::Serialize,
+    {
+        self.key = Some(key.serialize(MapKeySerializer)?);
+
+        Ok(())
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + ser::Serialize,
+    {
+        let key = self.key.take().ok_or_else(|| {
+            <Error as ser::Error>::custom("serialize_value called before serialize_key")
+        })?;
+
+        if is_skipped(value) {
+            return Ok(());
+        }
+
+        if self.dict.contains_key(&key) {
+            return Err(ErrorKind::DuplicateKey.into());
+        }
+
+        let value = value.serialize(ValueSerializer::new(self.options))?;
+        self.dict.insert(key, value);
+
+        Ok(())
+    }
+
+    fn end(self) -> Result<Value> {
+        Ok(tag_variant(self.variant, Value::Dict(self.dict)))
+    }
+}
+
+impl ser::SerializeMap for DictSerializer {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_key<T>(&mut self, key: &T) -> Result<()>
+    where
+        T: ?Sized + ser::Serialize,
+    {
+        DictSerializer::serialize_key(self, key)
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + ser::Serialize,
+    {
+        DictSerializer::serialize_value(self, value)
+    }
+
+    fn end(self) -> Result<Value> {
+        DictSerializer::end(self)
+    }
+}
+
+impl ser::SerializeStruct for DictSerializer {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<()>
+    where
+        T: ?Sized + ser::Serialize,
+    {
+        DictSerializer::serialize_key(self, key)?;
+        DictSerializer::serialize_value(self, value)
+    }
+
+    fn end(self) -> Result<Value> {
+        DictSerializer::end(self)
+    }
+}
+
+impl ser::SerializeStructVariant for DictSerializer {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<()>
+    where
+        T: ?Sized + ser::Serialize,
+    {
+        DictSerializer::serialize_key(self, key)?;
+        DictSerializer::serialize_value(self, value)
+    }
+
+    fn end(self) -> Result<Value> {
+        DictSerializer::end(self)
+    }
+}
+
+//////////////////////////////////////////////////////
+
+/// A deserializer of Rust values from a `Value`, the counterpart of the
+/// `Deserializer`, that consumes the value in memory.
+struct ValueDeserializer {
+    value: Value,
+    options: DeserializerOptions,
+}
+
+impl ValueDeserializer {
+    fn new(value: Value, options: DeserializerOptions) -> Self {
+        Self { value, options }
+    }
+
+    fn invalid_type(&self, expected: &dyn de::Expected) -> Error {
+        let unexpected = match &self.value {
+            Value::Integer(value) => match i64::try_from(*value) {
+                Ok(value) => de::Unexpected::Signed(value),
+                Err(_) => de::Unexpected::Other("integer"),
+            },
+            Value::Bytes(bytes) => de::Unexpected::Bytes(bytes),
+            Value::List(_) => de::Unexpected::Seq,
+            Value::Dict(_) => de::Unexpected::Map,
+        };
+
+        de::Error::invalid_type(unexpected, expected)
+    }
+
+    fn parse_integer<T>(self) -> Result<T>
+    where
+        T: TryFrom<i128>,
+    {
+        match self.value {
+            Value::Integer(value) => {
+                T::try_from(value).map_err(|_| ErrorKind::IntegerOverflow.into())
+            }
+            _ => Err(ErrorKind::ExpectedInteger.into()),
+        }
+    }
+
+    fn parse_float<T>(self) -> Result<T>
+    where
+        T: FloatCore + str::FromStr,
+    {
+        match (self.options.floats, self.value) {
+            (FloatEncoding::String, Value::Bytes(bytes)) => str::from_utf8(&bytes)
+                .ok()
+                .and_then(|string| string.parse::<T>().ok())
+                .ok_or_else(|| ErrorKind::ExpectedFloat.into()),
+            (FloatEncoding::FixedPoint(decimals), Value::Integer(scaled)) => {
+                let scaled = i64::try_from(scaled).map_err(|_| ErrorKind::IntegerOverflow)?;
+                let float = scaled as f64 / fixed_point_scale(decimals)?;
+
+                num_traits::cast(float).ok_or_else(|| ErrorKind::ExpectedFloat.into())
+            }
+            (FloatEncoding::FixedPoint(_), _) => Err(ErrorKind::ExpectedInteger.into()),
+            (FloatEncoding::String, _) => Err(ErrorKind::ExpectedFloat.into()),
+            (FloatEncoding::Error, _) => Err(ErrorKind::UnsupportedFloat.into()),
+        }
+    }
+
+    fn parse_bool(self) -> Result<bool> {
+        // Both of the encodings are accepted, unless in the strict mode.
+        let (integers, strings) = match self.options.bools {
+            BoolEncoding::String => (!self.options.strict, true),
+            BoolEncoding::Integer => (true, !self.options.strict),
+            BoolEncoding::Error => return Err(ErrorKind::UnsupportedBool.into()),
+        };
+
+        match self.value {
+            Value::Integer(1) if integers => Ok(true),
+            Value::Integer(0) if integers => Ok(false),
+            Value::Bytes(bytes) if strings && bytes == b"true" => Ok(true),
+            Value::Bytes(bytes) if strings && bytes == b"false" => Ok(false),
+            _ => Err(ErrorKind::UnknownType.into()),
+        }
+    }
+}
+
+macro_rules! fn_deserialize_integer {
+    ($method:ident, $visit:ident, $type:ty) => {
+        fn $method<V>(self, visitor: V) -> Result<V::Value>
+        where
+            V: de::Visitor<'de>,
+        {
+            visitor.$visit(self.parse_integer::<$type>()?)
+        }
+    };
+}
+
+impl<'de> de::Deserializer<'de> for ValueDeserializer {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.value {
+            Value::Integer(value) => visit_integer(value, visitor),
+            // Byte strings are visited as strings, when they are valid UTF-8,
+            // otherwise they are visited as raw bytes.
+            Value::Bytes(bytes) => match String::from_utf8(bytes) {
+                Ok(string) => visitor.visit_string(string),
+                Err(e) => visitor.visit_byte_buf(e.into_bytes()),
+            },
+            Value::List(list) => {
+                let mut list = ListDeserializer::new(list, self.options);
+                let value = visitor.visit_seq(&mut list)?;
+
+                if list.iter.len() == 0 {
+                    Ok(value)
+                } else {
+                    Err(ErrorKind::ExpectedListEnd.into())
+                }
+            }
+            Value::Dict(dict) => {
+                let mut dict = DictDeserializer::new(dict, self.options);
+                let value = visitor.visit_map(&mut dict)?;
+
+                if dict.iter.len() == 0 {
+                    Ok(value)
+                } else {
+                    Err(ErrorKind::ExpectedDictionaryEnd.into())
+                }
+            }
+        }
+    }
+
+    fn_deserialize_integer!(deserialize_u8, visit_u8, u8);
+    fn_deserialize_integer!(deserialize_u16, visit_u16, u16);
+    fn_deserialize_integer!(deserialize_u32, visit_u32, u32);
+    fn_deserialize_integer!(deserialize_u64, visit_u64, u64);
+    fn_deserialize_integer!(deserialize_u128, visit_u128, u128);
+
+    fn_deserialize_integer!(deserialize_i8, visit_i8, i8);
+    fn_deserialize_integer!(deserialize_i16, visit_i16, i16);
+    fn_deserialize_integer!(deserialize_i32, visit_i32, i32);
+    fn_deserialize_integer!(deserialize_i64, visit_i64, i64);
+    fn_deserialize_integer!(deserialize_i128, visit_i128, i128);
+
+    fn deserialize_str<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.value {
+            Value::Bytes(bytes) => match String::from_utf8(bytes) {
+                Ok(string) => visitor.visit_string(string),
+                Err(_) => Err(ErrorKind::InvalidUTF8.into()),
+            },
+            _ => Err(self.invalid_type(&visitor)),
+        }
+    }
+
+    fn deserialize_string<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_identifier<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_f32<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_f32(self.parse_float::<f32>()?)
+    }
+
+    fn deserialize_f64<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_f64(self.parse_float::<f64>()?)
+    }
+
+    fn deserialize_bool<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_bool(self.parse_bool()?)
+    }
+
+    fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.value {
+            Value::Bytes(bytes) => visitor.visit_byte_buf(bytes),
+            _ => Err(self.invalid_type(&visitor)),
+        }
+    }
+
+    fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.value {
+            Value::List(_) => self.deserialize_any(visitor),
+            _ => Err(ErrorKind::ExpectedList.into()),
+        }
+    }
+
+    fn deserialize_tuple<V>(self, _len: usize, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.value {
+            Value::Dict(_) => self.deserialize_any(visitor),
+            _ => Err(ErrorKind::ExpectedDictionary.into()),
+        }
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_map(visitor)
+    }
+
+    /// Raw values are given the encoded bytes of the value.
+    fn deserialize_newtype_struct<V>(self, name: &'static str, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        if name == raw::TOKEN {
+            visitor.visit_byte_buf(to_vec(&self.value)?)
+        } else if name == INT_BOOL_TOKEN {
+            visitor.visit_newtype_struct(ValueDeserializer::new(
+                self.value,
+                DeserializerOptions {
+                    bools: BoolEncoding::Integer,
+                    ..self.options
+                },
+            ))
+        } else {
+            visitor.visit_newtype_struct(self)
+        }
+    }
+
+    /// Enums are deserialized from the externally tagged representation, the
+    /// same way as by the `Deserializer`.
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.value {
+            Value::Bytes(bytes) => match String::from_utf8(bytes) {
+                Ok(variant) => visitor.visit_enum(variant.into_deserializer()),
+                Err(_) => Err(ErrorKind::InvalidUTF8.into()),
+            },
+            Value::Dict(mut dict) => match dict.pop_first() {
+                Some((variant, value)) if dict.is_empty() => visitor.visit_enum(EnumDeserializer {
+                    variant,
+                    value: ValueDeserializer::new(value, self.options),
+                }),
+                _ => Err(ErrorKind::ExpectedEnum.into()),
+            },
+            _ => Err(ErrorKind::ExpectedEnum.into()),
+        }
+    }
+
+    /// `None` is never represented, therefore any present value is `Some`.
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_some(self)
+    }
+
+    serde::forward_to_deserialize_any! {
+        char
+        unit unit_struct
+        ignored_any
+    }
+}
+
+struct ListDeserializer {
+    iter: vec::IntoIter<Value>,
+    options: DeserializerOptions,
+}
+
+impl ListDeserializer {
+    fn new(list: Vec<Value>, options: DeserializerOptions) -> Self {
+        Self {
+            iter: list.into_iter(),
+            options,
+        }
+    }
+}
+
+impl<'de> de::SeqAccess<'de> for ListDeserializer {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        match self.iter.next() {
+            Some(value) => seed
+                .deserialize(ValueDeserializer::new(value, self.options))
+                .map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.iter.len())
+    }
+}
+
+struct DictDeserializer {
+    iter: btree_map::IntoIter<Vec<u8>, Value>,
+    value: Option<Value>,
+    options: DeserializerOptions,
+}
+
+impl DictDeserializer {
+    fn new(dict: BTreeMap<Vec<u8>, Value>, options: DeserializerOptions) -> Self {
+        Self {
+            iter: dict.into_iter(),
+            value: None,
+            options,
+        }
+    }
+}
+
+impl<'de> de::MapAccess<'de> for DictDeserializer {
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>>
+    where
+        K: de::DeserializeSeed<'de>,
+    {
+        match self.iter.next() {
+            Some((key, value)) => {
+                self.value = Some(value);
+                seed.deserialize(KeyDeserializer { key }).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value>
+    where
+        V: de::DeserializeSeed<'de>,
+    {
+        let value = self.value.take().ok_or_else(|| {
+            <Error as de::Error>::custom("next_value_seed called before next_key_seed")
+        })?;
+
+        seed.deserialize(ValueDeserializer::new(value, self.options))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.iter.len())
+    }
+}
+
+/// A deserializer of dictionary keys from a `Value`, that follows the same
+/// rules as the keys of the `Deserializer`.
+struct KeyDeserializer {
+    key: Vec<u8>,
+}
+
+macro_rules! fn_deserialize_integer_key {
+    ($method:ident, $visit:ident, $type:ty, $consume:ident) => {
+        fn $method<V>(self, visitor: V) -> Result<V::Value>
+        where
+            V: de::Visitor<'de>,
+        {
+            visitor.$visit(integer_key($consume::<$type>(&self.key))?)
+        }
+    };
+}
+
+impl<'de> de::Deserializer<'de> for KeyDeserializer {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        match String::from_utf8(self.key) {
+            Ok(string) => visitor.visit_string(string),
+            Err(e) => visitor.visit_byte_buf(e.into_bytes()),
+        }
+    }
+
+    fn_deserialize_integer_key!(deserialize_u8, visit_u8, u8, consume_unsigned_number);
+    fn_deserialize_integer_key!(deserialize_u16, visit_u16, u16, consume_unsigned_number);
+    fn_deserialize_integer_key!(deserialize_u32, visit_u32, u32, consume_unsigned_number);
+    fn_deserialize_integer_key!(deserialize_u64, visit_u64, u64, consume_unsigned_number);
+    fn_deserialize_integer_key!(deserialize_u128, visit_u128, u128, consume_unsigned_number);
+
+    fn_deserialize_integer_key!(deserialize_i8, visit_i8, i8, consume_signed_number);
+    fn_deserialize_integer_key!(deserialize_i16, visit_i16, i16, consume_signed_number);
+    fn_deserialize_integer_key!(deserialize_i32, visit_i32, i32, consume_signed_number);
+    fn_deserialize_integer_key!(deserialize_i64, visit_i64, i64, consume_signed_number);
+    fn_deserialize_integer_key!(deserialize_i128, visit_i128, i128, consume_signed_number);
+
+    fn deserialize_str<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        match String::from_utf8(self.key) {
+            Ok(string) => visitor.visit_string(string),
+            Err(_) => Err(ErrorKind::InvalidUTF8.into()),
+        }
+    }
+
+    fn deserialize_string<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_byte_buf(self.key)
+    }
+
+    fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        let mut seq = de::value::SeqDeserializer::<_, Error>::new(self.key.into_iter());
+        let value = visitor.visit_seq(&mut seq)?;
+        seq.end()?;
+
+        Ok(value)
+    }
+
+    fn deserialize_tuple<V>(self, _len: usize, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        match String::from_utf8(self.key) {
+            Ok(variant) => visitor.visit_enum(variant.into_deserializer()),
+            Err(_) => Err(ErrorKind::InvalidUTF8.into()),
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool f32 f64 char unit unit_struct map struct identifier ignored_any
+    }
+}
+
+struct EnumDeserializer {
+    variant: Vec<u8>,
+    value: ValueDeserializer,
+}
+
+impl<'de> de::EnumAccess<'de> for EnumDeserializer {
+    type Error = Error;
+    type Variant = ValueDeserializer;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, ValueDeserializer)>
+    where
+        V: de::DeserializeSeed<'de>,
+    {
+        let variant = seed.deserialize(KeyDeserializer { key: self.variant })?;
+
+        Ok((variant, self.value))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for ValueDeserializer {
+    type Error = Error;
+
+    /// Unit variants are represented as a string only, never as a dictionary.
+    fn unit_variant(self) -> Result<()> {
+        Err(de::Error::invalid_type(
+            de::Unexpected::NewtypeVariant,
+            &"unit variant",
+        ))
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V>(self, _len: usize, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        de::Deserializer::deserialize_seq(self, visitor)
+    }
+
+    fn struct_variant<V>(self, _fields: &'static [&'static str], visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        de::Deserializer::deserialize_map(self, visitor)
+    }
+}
@@ -0,0 +1,128 @@
+#[cfg(test)]
+mod tests {
+    use std::net::{Ipv4Addr, Ipv6Addr, SocketAddrV4, SocketAddrV6};
+
+    use quickcheck_macros::quickcheck;
+    use serde_derive::{Deserialize, Serialize};
+
+    use bitrust_bencode::{bytes, from_slice, to_vec, Error, ErrorKind};
+
+    #[derive(Deserialize, Serialize, PartialEq, Debug)]
+    struct Announce {
+        #[serde(with = "bytes::fixed")]
+        info_hash: [u8; 20],
+        #[serde(with = "bytes::chunks")]
+        peers: Vec<SocketAddrV4>,
+        #[serde(with = "bytes::chunks")]
+        peers6: Vec<SocketAddrV6>,
+    }
+
+    #[derive(Deserialize, Serialize, PartialEq, Debug)]
+    struct Nodes {
+        #[serde(with = "bytes::chunks")]
+        nodes: Vec<([u8; 20], SocketAddrV4)>,
+        #[serde(with = "bytes::fixed")]
+        addr: SocketAddrV4,
+    }
+
+    #[test]
+    fn compact_peers() {
+        let announce = Announce {
+            info_hash: [0xaa; 20],
+            peers: vec![
+                SocketAddrV4::new(Ipv4Addr::new(10, 0, 0, 1), 6881),
+                SocketAddrV4::new(Ipv4Addr::new(192, 168, 1, 2), 51413),
+            ],
+            peers6: vec![SocketAddrV6::new(Ipv6Addr::LOCALHOST, 6881, 0, 0)],
+        };
+
+        let mut expected = b"d9:info_hash20:".to_vec();
+        expected.extend_from_slice(&[0xaa; 20]);
+        expected.extend_from_slice(b"5:peers12:\x0a\x00\x00\x01\x1a\xe1\xc0\xa8\x01\x02\xc8\xd5");
+        expected.extend_from_slice(b"6:peers618:");
+        expected.extend_from_slice(&Ipv6Addr::LOCALHOST.octets());
+        expected.extend_from_slice(b"\x1a\xe1e");
+
+        assert_eq!(expected, to_vec(&announce).unwrap());
+        assert_eq!(announce, from_slice(&expected).unwrap());
+    }
+
+    #[test]
+    fn compact_nodes() {
+        let mut input = b"d4:addr6:\x7f\x00\x00\x01\x1a\xe15:nodes52:".to_vec();
+        for i in 0..2 {
+            input.extend_from_slice(&[i; 20]);
+            input.extend_from_slice(&[1, 2, 3, 4, 0, i]);
+        }
+        input.push(b'e');
+
+        let nodes = from_slice::<Nodes>(&input).unwrap();
+        assert_eq!(
+            vec![
+                ([0; 20], SocketAddrV4::new(Ipv4Addr::new(1, 2, 3, 4), 0)),
+                ([1; 20], SocketAddrV4::new(Ipv4Addr::new(1, 2, 3, 4), 1)),
+            ],
+            nodes.nodes
+        );
+        assert_eq!(SocketAddrV4::new(Ipv4Addr::LOCALHOST, 6881), nodes.addr);
+        assert_eq!(input, to_vec(&nodes).unwrap());
+    }
+
+    #[test]
+    fn invalid_lengths() {
+        #[derive(Deserialize, Debug)]
+        struct Hash {
+            #[serde(with = "bytes::fixed")]
+            #[allow(dead_code)]
+            hash: [u8; 4],
+        }
+
+        #[derive(Deserialize, Debug)]
+        struct Peers {
+            #[serde(with = "bytes::chunks")]
+            #[allow(dead_code)]
+            peers: Vec<SocketAddrV4>,
+        }
+
+        assert!(from_slice::<Hash>(b"d4:hash4:abcde").is_ok());
+        assert!(matches!(
+            from_slice::<Hash>(b"d4:hash3:abce").map_err(Error::into_kind),
+            Err(ErrorKind::Message(_))
+        ));
+        assert!(from_slice::<Hash>(b"d4:hashli1eee").is_err());
+
+        assert!(from_slice::<Peers>(b"d5:peers0:e")
+            .unwrap()
+            .peers
+            .is_empty());
+        let error = from_slice::<Peers>(b"d5:peers7:abcdefge").unwrap_err();
+        assert_eq!(
+            "invalid length 7, expected a byte string of a multiple of 6 bytes",
+            error.kind().to_string()
+        );
+    }
+
+    #[quickcheck]
+    fn pieces(hashes: Vec<(u64, u64, u32)>) -> bool {
+        #[derive(Deserialize, Serialize, PartialEq, Debug)]
+        struct Info {
+            #[serde(with = "bytes::chunks")]
+            pieces: Vec<[u8; 20]>,
+        }
+
+        let info = Info {
+            pieces: hashes
+                .into_iter()
+                .map(|(a, b, c)| {
+                    let mut hash = [0; 20];
+                    hash[..8].copy_from_slice(&a.to_be_bytes());
+                    hash[8..16].copy_from_slice(&b.to_be_bytes());
+                    hash[16..].copy_from_slice(&c.to_be_bytes());
+                    hash
+                })
+                .collect(),
+        };
+
+        info == from_slice(&to_vec(&info).unwrap()).unwrap()
+    }
+}
@@ -0,0 +1,157 @@
+#[cfg(all(test, feature = "tokio"))]
+mod tests {
+    use bytes::BytesMut;
+    use futures_util::{SinkExt, StreamExt};
+    use serde_derive::{Deserialize, Serialize};
+    use tokio::io::{duplex, AsyncWriteExt};
+    use tokio_util::codec::{Decoder, Encoder, Framed, FramedRead, FramedWrite};
+
+    use bitrust_bencode::{
+        BencodeCodec, BoolEncoding, DeserializerOptions, Error, ErrorKind, FloatEncoding, Value,
+    };
+
+    #[derive(Deserialize, Serialize, PartialEq, Debug)]
+    struct Query {
+        t: String,
+        y: String,
+        q: String,
+    }
+
+    #[tokio::test]
+    async fn typed_messages() {
+        let (client, server) = duplex(64);
+        let mut client = Framed::new(client, BencodeCodec::<Query>::new());
+        let mut server = Framed::new(server, BencodeCodec::<Query>::new());
+
+        let ping = Query {
+            t: String::from("aa"),
+            y: String::from("q"),
+            q: String::from("ping"),
+        };
+
+        client.send(&ping).await.unwrap();
+        assert_eq!(ping, server.next().await.unwrap().unwrap());
+
+        server.send(&ping).await.unwrap();
+        assert_eq!(ping, client.next().await.unwrap().unwrap());
+    }
+
+    #[tokio::test]
+    async fn chunked_values() {
+        let (mut writer, reader) = duplex(8);
+        let mut reader = FramedRead::new(reader, BencodeCodec::<Value>::new());
+
+        tokio::spawn(async move {
+            // Several values split at arbitrary points.
+            for chunk in [&b"d1:ali1e"[..], b"2:bc", b"ee", b"i4", b"2e4:spam"] {
+                writer.write_all(chunk).await.unwrap();
+            }
+        });
+
+        let mut values = Vec::new();
+        while let Some(value) = reader.next().await {
+            values.push(value.unwrap());
+        }
+
+        assert_eq!(3, values.len());
+        assert_eq!(Some("bc"), values[0]["a"][1].as_str());
+        assert_eq!(Some(42), values[1].as_int());
+        assert_eq!(Some("spam"), values[2].as_str());
+    }
+
+    #[tokio::test]
+    async fn sorted_encoding() {
+        let (writer, reader) = duplex(64);
+        let mut writer = FramedWrite::new(writer, BencodeCodec::<Value>::new());
+        let mut reader = FramedRead::new(reader, BencodeCodec::<Value>::new());
+
+        writer
+            .send(&std::collections::HashMap::from([("b", 1), ("a", 2)]))
+            .await
+            .unwrap();
+        drop(writer);
+
+        let value = reader.next().await.unwrap().unwrap();
+        assert_eq!(
+            "d1:ai2e1:bi1ee",
+            bitrust_bencode::to_string(&value).unwrap()
+        );
+        assert!(reader.next().await.is_none());
+    }
+
+    #[test]
+    fn failed_encoding() {
+        let mut codec = BencodeCodec::<Value>::new();
+        let mut dst = BytesMut::new();
+
+        codec.encode(1, &mut dst).unwrap();
+        assert!(codec.encode(vec![Some(1), None], &mut dst).is_err());
+        assert_eq!(b"i1e", &dst[..]);
+    }
+
+    #[test]
+    fn encodings() {
+        let options = DeserializerOptions {
+            bools: BoolEncoding::Integer,
+            floats: FloatEncoding::FixedPoint(2),
+            ..DeserializerOptions::default()
+        };
+        let mut codec = BencodeCodec::<(bool, f64)>::with_options(options);
+        let mut buffer = BytesMut::new();
+
+        // The encoder writes the values the same way, as the decoder reads them.
+        codec.encode((true, 1.5), &mut buffer).unwrap();
+        assert_eq!(b"li1ei150ee", &buffer[..]);
+        assert_eq!(Some((true, 1.5)), codec.decode(&mut buffer).unwrap());
+    }
+
+    #[test]
+    fn claimed_lengths() {
+        let mut codec = BencodeCodec::<Value>::new();
+
+        // The length claimed by a peer is neither reserved nor buffered.
+        let mut buffer = BytesMut::from(&b"18446744073709551615:"[..]);
+        assert!(matches!(
+            codec.decode(&mut buffer).map_err(Error::into_kind),
+            Err(ErrorKind::InputTooLarge)
+        ));
+
+        let mut buffer = BytesMut::from(&b"99999999:"[..]);
+        assert!(matches!(
+            codec.decode(&mut buffer).map_err(Error::into_kind),
+            Err(ErrorKind::InputTooLarge)
+        ));
+
+        let mut buffer = BytesMut::from(&b"9999999:"[..]);
+        assert_eq!(None, codec.decode(&mut buffer).unwrap());
+        assert!(buffer.capacity() < 64 * 1024 + 64);
+    }
+
+    #[tokio::test]
+    async fn invalid_input() {
+        let (mut writer, reader) = duplex(64);
+        let mut reader = FramedRead::new(reader, BencodeCodec::<Value>::new());
+
+        writer.write_all(b"i1ex").await.unwrap();
+
+        assert_eq!(Some(1), reader.next().await.unwrap().unwrap().as_int());
+        assert!(matches!(
+            reader.next().await.unwrap().map_err(Error::into_kind),
+            Err(ErrorKind::UnknownType)
+        ));
+
+        let options = DeserializerOptions {
+            max_input_size: 8,
+            ..DeserializerOptions::default()
+        };
+        let (mut writer, reader) = duplex(64);
+        let mut reader = FramedRead::new(reader, BencodeCodec::<Value>::with_options(options));
+
+        writer.write_all(b"100:").await.unwrap();
+
+        assert!(matches!(
+            reader.next().await.unwrap().map_err(Error::into_kind),
+            Err(ErrorKind::InputTooLarge)
+        ));
+    }
+}
@@ -3,13 +3,19 @@ mod tests {
     use quickcheck_macros::quickcheck;
     use serde_derive::Deserialize;
 
-    use bitrust_bencode::{from_slice, from_str, Error};
+    #[cfg(feature = "std")]
+    use bitrust_bencode::{from_reader, from_reader_with_options};
+    use bitrust_bencode::{
+        from_slice, from_slice_prefix, from_slice_strict, from_slice_with_options, from_str,
+        BoolEncoding, Deserializer, DeserializerOptions, Error, ErrorKind, FloatEncoding, RawValue,
+        StreamDeserializer, Value,
+    };
 
     macro_rules! integer_test {
         ($method: ident, $type:ty) => {
             #[quickcheck]
             fn $method(value: $type) {
-                assert_eq!(value, from_str(&format!("i{}e", value)).unwrap())
+                assert_eq!(value, from_str::<$type>(&format!("i{}e", value)).unwrap())
             }
         };
     }
@@ -29,141 +35,142 @@ mod tests {
     #[test]
     fn integers_edge_cases() {
         assert!(matches!(
-            from_str::<usize>(r#"ie"#),
-            Err(Error::ExpectedUnsignedNumber)
+            from_str::<usize>(r#"ie"#).map_err(Error::into_kind),
+            Err(ErrorKind::ExpectedUnsignedNumber)
         ));
 
         assert!(matches!(
-            from_str::<usize>(r#"i1-23e"#),
-            Err(Error::ExpectedIntegerEnd)
+            from_str::<usize>(r#"i1-23e"#).map_err(Error::into_kind),
+            Err(ErrorKind::ExpectedIntegerEnd)
         ));
 
         assert!(matches!(
-            from_str::<usize>(r#"iasdfe"#),
-            Err(Error::ExpectedUnsignedNumber)
+            from_str::<usize>(r#"iasdfe"#).map_err(Error::into_kind),
+            Err(ErrorKind::ExpectedUnsignedNumber)
         ));
 
         assert!(matches!(
-            from_str::<usize>(r#"i e"#),
-            Err(Error::ExpectedUnsignedNumber)
+            from_str::<usize>(r#"i e"#).map_err(Error::into_kind),
+            Err(ErrorKind::ExpectedUnsignedNumber)
         ));
 
         assert!(matches!(
-            from_str::<u8>(r#"i-100e"#),
-            Err(Error::ExpectedUnsignedNumber)
+            from_str::<u8>(r#"i-100e"#).map_err(Error::into_kind),
+            Err(ErrorKind::ExpectedUnsignedNumber)
         ));
 
         assert!(matches!(
-            from_str::<usize>(r#"i123"#),
-            Err(Error::ExpectedIntegerEnd)
+            from_str::<usize>(r#"i123"#).map_err(Error::into_kind),
+            Err(ErrorKind::ExpectedIntegerEnd)
         ));
 
         assert!(matches!(
-            from_str::<usize>(r#"i123.456e"#),
-            Err(Error::ExpectedIntegerEnd)
+            from_str::<usize>(r#"i123.456e"#).map_err(Error::into_kind),
+            Err(ErrorKind::ExpectedIntegerEnd)
         ));
 
         assert!(matches!(
-            from_str::<isize>(r#"i-1.034e"#),
-            Err(Error::ExpectedIntegerEnd)
+            from_str::<isize>(r#"i-1.034e"#).map_err(Error::into_kind),
+            Err(ErrorKind::ExpectedIntegerEnd)
         ));
 
         assert!(matches!(
-            from_str::<usize>(r#"4:asdf"#),
-            Err(Error::ExpectedInteger)
+            from_str::<usize>(r#"4:asdf"#).map_err(Error::into_kind),
+            Err(ErrorKind::ExpectedInteger)
         ));
 
         assert!(matches!(
-            from_str::<usize>(r#"li123ee"#),
-            Err(Error::ExpectedInteger)
+            from_str::<usize>(r#"li123ee"#).map_err(Error::into_kind),
+            Err(ErrorKind::ExpectedInteger)
         ));
 
         assert!(matches!(
-            from_str::<usize>(r#"d1:ai323ee"#),
-            Err(Error::ExpectedInteger)
+            from_str::<usize>(r#"d1:ai323ee"#).map_err(Error::into_kind),
+            Err(ErrorKind::ExpectedInteger)
         ));
 
         assert!(matches!(
-            from_str::<usize>(r#"i123etrailing"#),
-            Err(Error::TrailingCharacters)
+            from_str::<usize>(r#"i123etrailing"#).map_err(Error::into_kind),
+            Err(ErrorKind::TrailingCharacters)
         ));
     }
 
     #[test]
+    #[allow(clippy::legacy_numeric_constants)]
     fn integers_near_bounds() {
         // Happy paths.
         assert_eq!(
             std::u8::MAX,
-            from_str(format!("i{}e", std::u8::MAX).as_str()).unwrap()
+            from_str::<u8>(format!("i{}e", std::u8::MAX).as_str()).unwrap()
         );
         assert_eq!(
             std::u16::MAX,
-            from_str(format!("i{}e", std::u16::MAX).as_str()).unwrap()
+            from_str::<u16>(format!("i{}e", std::u16::MAX).as_str()).unwrap()
         );
         assert_eq!(
             std::u32::MAX,
-            from_str(format!("i{}e", std::u32::MAX).as_str()).unwrap()
+            from_str::<u32>(format!("i{}e", std::u32::MAX).as_str()).unwrap()
         );
         assert_eq!(
             std::u64::MAX,
-            from_str(format!("i{}e", std::u64::MAX).as_str()).unwrap()
+            from_str::<u64>(format!("i{}e", std::u64::MAX).as_str()).unwrap()
         );
         assert_eq!(
             std::i8::MAX,
-            from_str(format!("i{}e", std::i8::MAX).as_str()).unwrap()
+            from_str::<i8>(format!("i{}e", std::i8::MAX).as_str()).unwrap()
         );
         assert_eq!(
             std::i16::MAX,
-            from_str(format!("i{}e", std::i16::MAX).as_str()).unwrap()
+            from_str::<i16>(format!("i{}e", std::i16::MAX).as_str()).unwrap()
         );
         assert_eq!(
             std::i32::MAX,
-            from_str(format!("i{}e", std::i32::MAX).as_str()).unwrap()
+            from_str::<i32>(format!("i{}e", std::i32::MAX).as_str()).unwrap()
         );
         assert_eq!(
             std::i64::MAX,
-            from_str(format!("i{}e", std::i64::MAX).as_str()).unwrap()
+            from_str::<i64>(format!("i{}e", std::i64::MAX).as_str()).unwrap()
         );
 
         // Unhappy paths.
         assert!(matches!(
-            from_str::<u8>(format!("i{}0e", std::u8::MAX).as_str()),
-            Err(Error::IntegerOverflow),
+            from_str::<u8>(format!("i{}0e", std::u8::MAX).as_str()).map_err(Error::into_kind),
+            Err(ErrorKind::IntegerOverflow),
         ));
 
         assert!(matches!(
-            from_str::<u16>(format!("i{}0e", std::u16::MAX).as_str()),
-            Err(Error::IntegerOverflow),
+            from_str::<u16>(format!("i{}0e", std::u16::MAX).as_str()).map_err(Error::into_kind),
+            Err(ErrorKind::IntegerOverflow),
         ));
 
         assert!(matches!(
-            from_str::<u32>(format!("i{}0e", std::u32::MAX).as_str()),
-            Err(Error::IntegerOverflow),
+            from_str::<u32>(format!("i{}0e", std::u32::MAX).as_str()).map_err(Error::into_kind),
+            Err(ErrorKind::IntegerOverflow),
         ));
 
         assert!(matches!(
-            from_str::<u64>(format!("i{}0e", std::u64::MAX).as_str()),
-            Err(Error::IntegerOverflow),
+            from_str::<u64>(format!("i{}0e", std::u64::MAX).as_str()).map_err(Error::into_kind),
+            Err(ErrorKind::IntegerOverflow),
         ));
 
         assert!(matches!(
-            from_str::<i8>(format!("i{}0e", std::i8::MAX).as_str()),
-            Err(Error::IntegerOverflow),
+            from_str::<i8>(format!("i{}0e", std::i8::MAX).as_str()).map_err(Error::into_kind),
+            Err(ErrorKind::IntegerOverflow),
         ));
 
         assert!(matches!(
-            from_str::<i16>(format!("i{}0e", std::i16::MAX).as_str()),
-            Err(Error::IntegerOverflow),
+            from_str::<i16>(format!("i{}0e", std::i16::MAX).as_str()).map_err(Error::into_kind),
+            Err(ErrorKind::IntegerOverflow),
         ));
 
         assert!(matches!(
-            from_str::<i32>(format!("i{}0e", std::i32::MAX).as_str()),
-            Err(Error::IntegerOverflow),
+            from_str::<i32>(format!("i{}0e", std::i32::MAX).as_str()).map_err(Error::into_kind),
+            Err(ErrorKind::IntegerOverflow),
         ));
 
         assert!(matches!(
-            from_str::<i64>(format!("i{}0e", std::i64::MAX).as_str()),
-            Err(Error::IntegerOverflow),
+            from_str::<i64>(format!("i{}0e", std::i64::MAX).as_str()).map_err(Error::into_kind),
+            Err(ErrorKind::IntegerOverflow),
         ));
     }
 
@@ -176,6 +183,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::bool_assert_comparison)]
     fn bools() {
         assert_eq!(true, from_str::<bool>("4:true").unwrap());
         assert_eq!(false, from_str::<bool>("5:false").unwrap());
@@ -183,36 +191,39 @@ mod tests {
 
     #[test]
     fn strings_edge_cases() {
-        assert!(matches!(from_str::<&str>(r#"4:EOF"#), Err(Error::EOF)));
+        assert!(matches!(
+            from_str::<&str>(r#"4:EOF"#).map_err(Error::into_kind),
+            Err(ErrorKind::EOF)
+        ));
 
         assert!(matches!(
-            from_str::<&str>(r#"string"#),
-            Err(Error::ExpectedUnsignedNumber)
+            from_str::<&str>(r#"string"#).map_err(Error::into_kind),
+            Err(ErrorKind::ExpectedUnsignedNumber)
         ));
 
         assert!(matches!(
-            from_str::<&str>(r#"nointeger:value"#),
-            Err(Error::ExpectedUnsignedNumber)
+            from_str::<&str>(r#"nointeger:value"#).map_err(Error::into_kind),
+            Err(ErrorKind::ExpectedUnsignedNumber)
         ));
 
         assert!(matches!(
-            from_str::<&str>(r#"i123e"#),
-            Err(Error::ExpectedUnsignedNumber),
+            from_str::<&str>(r#"i123e"#).map_err(Error::into_kind),
+            Err(ErrorKind::ExpectedUnsignedNumber),
         ));
 
         assert!(matches!(
-            from_str::<&str>(r#"l2:abe"#),
-            Err(Error::ExpectedUnsignedNumber),
+            from_str::<&str>(r#"l2:abe"#).map_err(Error::into_kind),
+            Err(ErrorKind::ExpectedUnsignedNumber),
         ));
 
         assert!(matches!(
-            from_str::<&str>(r#"d1:ae"#),
-            Err(Error::ExpectedUnsignedNumber),
+            from_str::<&str>(r#"d1:ae"#).map_err(Error::into_kind),
+            Err(ErrorKind::ExpectedUnsignedNumber),
         ));
 
         assert!(matches!(
-            from_str::<&str>(r#"3:keytrailing"#),
-            Err(Error::TrailingCharacters)
+            from_str::<&str>(r#"3:keytrailing"#).map_err(Error::into_kind),
+            Err(ErrorKind::TrailingCharacters)
         ));
     }
 
@@ -222,7 +233,7 @@ mod tests {
             fn $method(value: $type) {
                 assert_eq!(
                     value,
-                    from_str(&format!("{}:{}", value.to_string().len(), value)).unwrap()
+                    from_str::<$type>(&format!("{}:{}", value.to_string().len(), value)).unwrap()
                 )
             }
         };
@@ -234,18 +245,18 @@ mod tests {
     #[test]
     fn floats_edge_cases() {
         assert!(matches!(
-            from_str::<f64>(r#"7:invalid"#),
-            Err(Error::ExpectedFloat)
+            from_str::<f64>(r#"7:invalid"#).map_err(Error::into_kind),
+            Err(ErrorKind::ExpectedFloat)
         ));
 
         assert!(matches!(
-            from_str::<f64>(r#"3:-0a"#),
-            Err(Error::ExpectedFloat)
+            from_str::<f64>(r#"3:-0a"#).map_err(Error::into_kind),
+            Err(ErrorKind::ExpectedFloat)
         ));
 
         assert!(matches!(
-            from_str::<f64>(r#"0:"#),
-            Err(Error::ExpectedFloat)
+            from_str::<f64>(r#"0:"#).map_err(Error::into_kind),
+            Err(ErrorKind::ExpectedFloat)
         ));
     }
 
@@ -274,8 +285,9 @@ mod tests {
         //
         // This sequence has an invalid code point 0xf0, therefore it should fail.
         assert!(matches!(
-            from_slice::<&str>(&[0x36, 0x3a, 0x48, 0x65, 0xf0, 0x6c, 0x6c, 0x6f]),
-            Err(Error::InvalidUTF8)
+            from_slice::<&str>(&[0x36, 0x3a, 0x48, 0x65, 0xf0, 0x6c, 0x6c, 0x6f])
+                .map_err(Error::into_kind),
+            Err(ErrorKind::InvalidUTF8)
         ));
     }
 
@@ -378,4 +390,546 @@ mod tests {
         // Expecting a valid deserialization, therefore shouldn't throw any errors.
         from_slice::<TorrentMetainfo>(f).unwrap();
     }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn readers() {
+        use std::{collections::HashMap, io::Read};
+
+        assert_eq!(
+            vec![1, 2, 3],
+            from_reader::<_, Vec<u32>>("li1ei2ei3ee".as_bytes()).unwrap()
+        );
+
+        assert_eq!(
+            HashMap::from([(String::from("a"), String::from("b"))]),
+            from_reader::<_, HashMap<String, String>>(std::io::Cursor::new(b"d1:a1:be")).unwrap()
+        );
+
+        // Consecutive values are read one by one.
+        let mut reader = "li1eei2e".as_bytes();
+        assert_eq!(vec![1], from_reader::<_, Vec<u32>>(&mut reader).unwrap());
+        assert_eq!(2, from_reader::<_, u32>(&mut reader).unwrap());
+        assert!(reader.is_empty());
+
+        // The stream doesn't need to end after the value.
+        let reader = "d1:ai42ee".as_bytes().chain(std::io::repeat(b'x'));
+        assert_eq!(
+            HashMap::from([(String::from("a"), 42)]),
+            from_reader::<_, HashMap<String, u32>>(reader).unwrap()
+        );
+
+        assert!(matches!(
+            from_reader::<_, Vec<u32>>("li1e5:ab".as_bytes()).map_err(Error::into_kind),
+            Err(ErrorKind::EOF)
+        ));
+
+        // Lengths claimed by the input are not allocated up front.
+        assert!(matches!(
+            from_reader::<_, Vec<u8>>("18446744073709551615:".as_bytes()).map_err(Error::into_kind),
+            Err(ErrorKind::InputTooLarge)
+        ));
+        assert!(matches!(
+            from_reader::<_, Vec<u8>>("99999999999999:".as_bytes()).map_err(Error::into_kind),
+            Err(ErrorKind::EOF)
+        ));
+
+        struct FailingReader;
+
+        impl std::io::Read for FailingReader {
+            fn read(&mut self, _buf: &mut [u8]) -> std::io::Result<usize> {
+                Err(std::io::Error::other("closed"))
+            }
+        }
+
+        assert!(matches!(
+            from_reader::<_, u32>(FailingReader).map_err(Error::into_kind),
+            Err(ErrorKind::IO(_))
+        ));
+    }
+
+    #[test]
+    fn strict_integers() {
+        for input in ["i0e", "i1e", "i-1e", "i10e", "i-1234567890e"] {
+            assert!(from_slice_strict::<i64>(input.as_bytes()).is_ok());
+            assert!(from_slice_strict::<Value>(input.as_bytes()).is_ok());
+        }
+
+        for input in [
+            "i-0e", "i03e", "i-03e", "i00e", "i--1e", "i1-2e", "i-e", "i1-e",
+        ] {
+            // Lenient mode accepts some of these, strict mode rejects all.
+            assert!(
+                matches!(
+                    from_slice_strict::<i64>(input.as_bytes()).map_err(Error::into_kind),
+                    Err(ErrorKind::NonCanonicalInteger)
+                ),
+                "{}",
+                input
+            );
+            assert!(
+                matches!(
+                    from_slice_strict::<Value>(input.as_bytes()).map_err(Error::into_kind),
+                    Err(ErrorKind::NonCanonicalInteger)
+                ),
+                "{}",
+                input
+            );
+        }
+
+        assert_eq!(3, from_str::<i64>("i03e").unwrap());
+        assert_eq!(0, from_str::<i64>("i-0e").unwrap());
+        assert!(matches!(
+            from_slice_strict::<u64>(b"i007e").map_err(Error::into_kind),
+            Err(ErrorKind::NonCanonicalInteger)
+        ));
+
+        // Raw & skipped integers are checked the same way as parsed ones.
+        for input in ["i-e", "i--1e", "i1-e", "d1:ai1-ee"] {
+            assert!(
+                matches!(
+                    from_str::<RawValue>(input).map_err(Error::into_kind),
+                    Err(ErrorKind::ExpectedSignedNumber)
+                ),
+                "{}",
+                input
+            );
+        }
+        assert!(matches!(
+            from_str::<serde::de::IgnoredAny>("li--1ee").map_err(Error::into_kind),
+            Err(ErrorKind::ExpectedSignedNumber)
+        ));
+    }
+
+    #[test]
+    fn strict_strings() {
+        assert_eq!("", from_slice_strict::<&str>(b"0:").unwrap());
+        assert_eq!(
+            "abcdefghij",
+            from_slice_strict::<&str>(b"10:abcdefghij").unwrap()
+        );
+
+        assert_eq!("abc", from_str::<&str>("03:abc").unwrap());
+        assert!(matches!(
+            from_slice_strict::<&str>(b"03:abc").map_err(Error::into_kind),
+            Err(ErrorKind::NonCanonicalStringLength)
+        ));
+        assert!(matches!(
+            from_slice_strict::<&[u8]>(b"00:").map_err(Error::into_kind),
+            Err(ErrorKind::NonCanonicalStringLength)
+        ));
+        assert!(matches!(
+            from_slice_strict::<Value>(b"l03:abce").map_err(Error::into_kind),
+            Err(ErrorKind::NonCanonicalStringLength)
+        ));
+    }
+
+    #[test]
+    fn strict_dictionaries() {
+        use std::collections::HashMap;
+
+        #[derive(Deserialize, Debug)]
+        struct Test {
+            #[allow(dead_code)]
+            a: u8,
+            #[allow(dead_code)]
+            b: u8,
+        }
+
+        assert!(from_slice_strict::<Test>(b"d1:ai1e1:bi2ee").is_ok());
+        assert!(from_slice_strict::<HashMap<&str, u8>>(b"d1:ai1e2:aai2e1:bi3ee").is_ok());
+
+        assert!(from_str::<Test>("d1:bi2e1:ai1ee").is_ok());
+        assert!(matches!(
+            from_slice_strict::<Test>(b"d1:bi2e1:ai1ee").map_err(Error::into_kind),
+            Err(ErrorKind::UnsortedKeys)
+        ));
+        assert!(matches!(
+            from_slice_strict::<Value>(b"d2:aai1e1:ai2ee").map_err(Error::into_kind),
+            Err(ErrorKind::UnsortedKeys)
+        ));
+        assert!(matches!(
+            from_slice_strict::<HashMap<&str, u8>>(b"d1:ai1e1:ai2ee").map_err(Error::into_kind),
+            Err(ErrorKind::DuplicateKey)
+        ));
+
+        // Nested dictionaries & skipped values are checked as well.
+        assert!(matches!(
+            from_slice_strict::<Value>(b"d1:ad1:bi1e1:ai2eee").map_err(Error::into_kind),
+            Err(ErrorKind::UnsortedKeys)
+        ));
+        assert!(matches!(
+            from_slice_strict::<RawValue>(b"ld1:ai1e1:ai2eee").map_err(Error::into_kind),
+            Err(ErrorKind::DuplicateKey)
+        ));
+    }
+
+    #[test]
+    fn strict_from_file() {
+        use std::env;
+        use std::fs;
+        use std::path::Path;
+
+        let mut dir = env::current_dir().unwrap();
+        dir.push(Path::new(
+            "tests/data/ubuntu-19.10-desktop-amd64.iso.torrent",
+        ));
+        let f = &fs::read(dir).unwrap();
+
+        // The file is canonically encoded, therefore it should be accepted.
+        from_slice_strict::<Value>(f).unwrap();
+    }
+
+    #[test]
+    fn error_locations() {
+        #[derive(Deserialize, Debug)]
+        struct File {
+            #[allow(dead_code)]
+            length: u64,
+        }
+
+        #[derive(Deserialize, Debug)]
+        struct Info {
+            #[allow(dead_code)]
+            files: Vec<File>,
+        }
+
+        #[derive(Deserialize, Debug)]
+        struct Metainfo {
+            #[allow(dead_code)]
+            info: Info,
+        }
+
+        let input = "d4:infod5:filesld6:lengthi1eed6:lengthi-1eeeee";
+        let error = from_str::<Metainfo>(input).unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::ExpectedUnsignedNumber));
+        assert_eq!(Some("info.files[1].length"), error.path());
+        assert_eq!(input.find("i-1e"), error.position());
+        assert_eq!(
+            format!(
+                "Expected unsigned number at byte {} in `info.files[1].length`",
+                input.find("i-1e").unwrap()
+            ),
+            error.to_string()
+        );
+
+        // Errors of the visitors are located at the end of their value.
+        let input = "d4:infod5:filesld6:lengthi1eedeeee";
+        let error = from_str::<Metainfo>(input).unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::Message(_)));
+        assert_eq!(Some("info.files[1]"), error.path());
+        assert_eq!(Some(input.len() - 4), error.position());
+
+        // Errors at the top-level value have no path.
+        let error = from_str::<Vec<u8>>("li1ei2e").unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::EOF));
+        assert_eq!(None, error.path());
+        assert_eq!(Some(7), error.position());
+
+        let error = from_str::<u8>("i1ei2e").unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::TrailingCharacters));
+        assert_eq!(Some(3), error.position());
+
+        let error = from_slice_strict::<Value>(b"d1:ad1:bi1e1:ai2eee").unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::UnsortedKeys));
+        assert_eq!(Some("a"), error.path());
+        assert_eq!(Some(11), error.position());
+    }
+
+    #[test]
+    fn limits() {
+        // Deeply nested input must not overflow the stack.
+        let nested = format!("{}{}", "l".repeat(100_000), "e".repeat(100_000));
+        assert!(matches!(
+            from_str::<Value>(&nested).map_err(Error::into_kind),
+            Err(ErrorKind::DepthLimitExceeded)
+        ));
+        assert!(matches!(
+            RawValue::from_slice(nested.as_bytes()).map_err(Error::into_kind),
+            Err(ErrorKind::DepthLimitExceeded)
+        ));
+
+        let options = DeserializerOptions {
+            max_depth: 2,
+            ..DeserializerOptions::default()
+        };
+        assert!(from_slice_with_options::<Value>(b"lld1:ai1eeee", options).is_err());
+        assert!(from_slice_with_options::<Value>(b"llee", options).is_ok());
+        assert!(matches!(
+            from_slice_with_options::<Value>(b"llleee", options).map_err(Error::into_kind),
+            Err(ErrorKind::DepthLimitExceeded)
+        ));
+        assert!(matches!(
+            from_slice_with_options::<Value>(b"d1:ad1:bd1:ci1eeee", options)
+                .map_err(Error::into_kind),
+            Err(ErrorKind::DepthLimitExceeded)
+        ));
+
+        let options = DeserializerOptions {
+            max_string_length: 4,
+            ..DeserializerOptions::default()
+        };
+        assert!(from_slice_with_options::<&str>(b"4:spam", options).is_ok());
+        assert!(matches!(
+            from_slice_with_options::<&str>(b"99999999999:", options).map_err(Error::into_kind),
+            Err(ErrorKind::StringTooLong)
+        ));
+        assert!(matches!(
+            from_slice_with_options::<Value>(b"d5:spamsi1ee", options).map_err(Error::into_kind),
+            Err(ErrorKind::StringTooLong)
+        ));
+
+        let options = DeserializerOptions {
+            max_items: 3,
+            ..DeserializerOptions::default()
+        };
+        assert!(from_slice_with_options::<Value>(b"li1ed1:ai2eeli3eee", options).is_err());
+        assert!(from_slice_with_options::<Value>(b"li1ed1:ai2eee", options).is_ok());
+        assert!(matches!(
+            from_slice_with_options::<Vec<u8>>(b"li1ei2ei3ei4ee", options)
+                .map_err(Error::into_kind),
+            Err(ErrorKind::TooManyItems)
+        ));
+        assert!(matches!(
+            from_slice_with_options::<Value>(b"d1:ai1e1:bi2e1:ci3e1:di4ee", options)
+                .map_err(Error::into_kind),
+            Err(ErrorKind::TooManyItems)
+        ));
+
+        let options = DeserializerOptions {
+            max_input_size: 4,
+            ..DeserializerOptions::default()
+        };
+        assert!(from_slice_with_options::<u8>(b"i10e", options).is_ok());
+        assert!(matches!(
+            from_slice_with_options::<u8>(b"i100e", options).map_err(Error::into_kind),
+            Err(ErrorKind::InputTooLarge)
+        ));
+        #[cfg(feature = "std")]
+        assert!(matches!(
+            from_reader_with_options::<_, u8>(std::io::repeat(b'1'), options)
+                .map_err(Error::into_kind),
+            Err(ErrorKind::InputTooLarge)
+        ));
+
+        let options = DeserializerOptions {
+            strict: true,
+            ..DeserializerOptions::default()
+        };
+        assert!(matches!(
+            from_slice_with_options::<u8>(b"i01e", options).map_err(Error::into_kind),
+            Err(ErrorKind::NonCanonicalInteger)
+        ));
+    }
+
+    #[test]
+    fn prefixes() {
+        #[derive(Deserialize, PartialEq, Debug)]
+        struct Metadata {
+            msg_type: u8,
+            piece: u32,
+        }
+
+        let (metadata, rest) =
+            from_slice_prefix::<Metadata>(b"d8:msg_typei1e5:piecei3eepiece data").unwrap();
+        assert_eq!(
+            Metadata {
+                msg_type: 1,
+                piece: 3
+            },
+            metadata
+        );
+        assert_eq!(b"piece data", rest);
+
+        let (value, rest) = from_slice_prefix::<i64>(b"i42e").unwrap();
+        assert_eq!(42, value);
+        assert!(rest.is_empty());
+
+        assert!(matches!(
+            from_slice_prefix::<Metadata>(b"d8:msg_typei1e").map_err(Error::into_kind),
+            Err(ErrorKind::EOF)
+        ));
+
+        let mut de = Deserializer::new(b"4:spami1e");
+        assert_eq!(
+            "spam",
+            <&str as serde::Deserialize>::deserialize(&mut de).unwrap()
+        );
+        assert_eq!(b"i1e", de.remaining());
+
+        let error = de.end().unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::TrailingCharacters));
+        assert_eq!(Some(6), error.position());
+    }
+
+    #[test]
+    fn streams() {
+        let values = StreamDeserializer::<Value>::new(b"i1e4:spamli2eede")
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(
+            vec![
+                Value::from(1),
+                Value::from("spam"),
+                Value::from(vec![Value::from(2)]),
+                Value::Dict(Default::default()),
+            ],
+            values
+        );
+
+        let mut stream = Deserializer::new(b"i1ei2ex").into_stream::<i64>();
+        assert_eq!(1, stream.next().unwrap().unwrap());
+        assert_eq!(3, stream.byte_offset());
+        assert_eq!(2, stream.next().unwrap().unwrap());
+        assert_eq!(b"x", stream.remaining());
+
+        // The iteration stops after the first error.
+        let error = stream.next().unwrap().unwrap_err();
+        assert_eq!(Some(6), error.position());
+        assert!(stream.next().is_none());
+
+        assert!(StreamDeserializer::<Value>::new(b"").next().is_none());
+    }
+
+    #[test]
+    fn byte_keys() {
+        use std::collections::{BTreeMap, HashMap};
+
+        use serde_bytes::ByteBuf;
+
+        #[derive(Deserialize, PartialEq, Eq, PartialOrd, Ord, Debug)]
+        struct InfoHash([u8; 4]);
+
+        let data = b"d4:\x00\x01\x02\x03i1e4:\xff\xfe\xfd\xfci2ee";
+
+        let map = from_slice::<BTreeMap<ByteBuf, i64>>(data).unwrap();
+        assert_eq!(
+            Some(&2),
+            map.get(&ByteBuf::from(vec![0xff, 0xfe, 0xfd, 0xfc]))
+        );
+
+        let map = from_slice::<BTreeMap<Vec<u8>, i64>>(data).unwrap();
+        assert_eq!(Some(&1), map.get([0, 1, 2, 3].as_slice()));
+
+        let map = from_slice::<HashMap<&[u8], i64>>(data).unwrap();
+        assert_eq!(Some(&2), map.get(b"\xff\xfe\xfd\xfc".as_slice()));
+
+        let map = from_slice::<BTreeMap<[u8; 4], i64>>(data).unwrap();
+        assert_eq!(Some(&1), map.get(&[0, 1, 2, 3]));
+
+        let map = from_slice::<BTreeMap<InfoHash, i64>>(data).unwrap();
+        assert_eq!(Some(&2), map.get(&InfoHash([0xff, 0xfe, 0xfd, 0xfc])));
+
+        assert!(matches!(
+            from_slice::<BTreeMap<String, i64>>(data).map_err(Error::into_kind),
+            Err(ErrorKind::InvalidUTF8)
+        ));
+        assert!(from_slice::<BTreeMap<[u8; 3], i64>>(data).is_err());
+        assert!(from_slice::<BTreeMap<[u8; 5], i64>>(data).is_err());
+
+        // Integer keys are parsed from their string representation.
+        let map = from_slice::<BTreeMap<u32, &str>>(b"d1:04:zero2:103:tene").unwrap();
+        assert_eq!(Some(&"ten"), map.get(&10));
+        assert!(matches!(
+            from_slice::<BTreeMap<u32, &str>>(b"d1:x1:ye").map_err(Error::into_kind),
+            Err(ErrorKind::ExpectedInteger)
+        ));
+
+        // The same rules apply as to integer values.
+        for key in [&b"2:+5"[..], b"2:-5", b"0:", b"3:5 x"] {
+            let data = [b"d", key, b"1:ye"].concat();
+            assert!(matches!(
+                from_slice::<BTreeMap<u32, &str>>(&data).map_err(Error::into_kind),
+                Err(ErrorKind::ExpectedInteger)
+            ));
+        }
+        assert_eq!(
+            Some(&"y"),
+            from_slice::<BTreeMap<i32, &str>>(b"d2:-51:ye")
+                .unwrap()
+                .get(&-5)
+        );
+        assert!(matches!(
+            from_slice::<BTreeMap<u8, &str>>(b"d3:2561:ye").map_err(Error::into_kind),
+            Err(ErrorKind::IntegerOverflow)
+        ));
+        assert!(from_slice::<BTreeMap<u32, &str>>(b"d2:051:ye").is_ok());
+        assert!(matches!(
+            from_slice_strict::<BTreeMap<u32, &str>>(b"d2:051:ye").map_err(Error::into_kind),
+            Err(ErrorKind::NonCanonicalInteger)
+        ));
+        assert!(matches!(
+            from_slice_strict::<BTreeMap<i32, &str>>(b"d2:-01:ye").map_err(Error::into_kind),
+            Err(ErrorKind::NonCanonicalInteger)
+        ));
+    }
+
+    #[test]
+    fn encodings() {
+        // Both of the bool encodings are accepted in the lenient mode.
+        assert!(from_str::<bool>("i1e").unwrap());
+        assert!(!from_str::<bool>("i0e").unwrap());
+        assert!(matches!(
+            from_str::<bool>("i2e").map_err(Error::into_kind),
+            Err(ErrorKind::UnknownType)
+        ));
+
+        let options = DeserializerOptions {
+            strict: true,
+            bools: BoolEncoding::Integer,
+            ..DeserializerOptions::default()
+        };
+        assert!(from_slice_with_options::<bool>(b"i1e", options).unwrap());
+        assert!(matches!(
+            from_slice_with_options::<bool>(b"4:true", options).map_err(Error::into_kind),
+            Err(ErrorKind::ExpectedInteger)
+        ));
+        assert!(matches!(
+            from_slice_strict::<bool>(b"i1e").map_err(Error::into_kind),
+            Err(ErrorKind::ExpectedUnsignedNumber)
+        ));
+
+        let options = DeserializerOptions {
+            floats: FloatEncoding::FixedPoint(3),
+            ..DeserializerOptions::default()
+        };
+        assert_eq!(
+            1.5,
+            from_slice_with_options::<f32>(b"i1500e", options).unwrap()
+        );
+        assert_eq!(
+            -0.002,
+            from_slice_with_options::<f64>(b"i-2e", options).unwrap()
+        );
+        assert!(matches!(
+            from_slice_with_options::<f64>(b"3:1.5", options).map_err(Error::into_kind),
+            Err(ErrorKind::ExpectedInteger)
+        ));
+
+        // The number of decimal places is out of the range of the scale.
+        for decimals in [400, u32::MAX] {
+            let options = DeserializerOptions {
+                floats: FloatEncoding::FixedPoint(decimals),
+                ..DeserializerOptions::default()
+            };
+            assert!(matches!(
+                from_slice_with_options::<f64>(b"i1500e", options).map_err(Error::into_kind),
+                Err(ErrorKind::UnsupportedFloat)
+            ));
+        }
+
+        let options = DeserializerOptions {
+            bools: BoolEncoding::Error,
+            floats: FloatEncoding::Error,
+            ..DeserializerOptions::default()
+        };
+        assert!(matches!(
+            from_slice_with_options::<bool>(b"i1e", options).map_err(Error::into_kind),
+            Err(ErrorKind::UnsupportedBool)
+        ));
+        assert!(matches!(
+            from_slice_with_options::<f64>(b"3:1.5", options).map_err(Error::into_kind),
+            Err(ErrorKind::UnsupportedFloat)
+        ));
+    }
 }
@@ -0,0 +1,181 @@
+#[cfg(all(test, feature = "derive"))]
+mod tests {
+    use std::net::SocketAddrV4;
+
+    use bitrust_bencode::{
+        from_slice, from_slice_strict, from_str, from_value, to_string, to_value, to_vec,
+        to_vec_with_options, Bencode, Error, ErrorKind, Extra, SerializerOptions, Value,
+    };
+
+    #[derive(Bencode, PartialEq, Debug)]
+    struct Info {
+        #[bencode(bytes)]
+        pieces: Vec<u8>,
+        name: String,
+        #[bencode(rename = "piece length")]
+        piece_length: u64,
+        #[bencode(int_bool)]
+        private: Option<bool>,
+        #[bencode(flatten_unknown)]
+        extra: Extra,
+    }
+
+    #[derive(Bencode, PartialEq, Debug)]
+    struct Torrent {
+        announce: String,
+        info: Info,
+        comment: Option<String>,
+        #[bencode(flatten_unknown)]
+        extra: Extra,
+    }
+
+    #[derive(Bencode, PartialEq, Debug)]
+    struct Node {
+        #[bencode(bytes)]
+        id: [u8; 20],
+        #[bencode(int_bool)]
+        seed: bool,
+        #[bencode(bytes)]
+        token: Option<[u8; 4]>,
+    }
+
+    mod renamed {
+        pub use bitrust_bencode::*;
+    }
+
+    #[derive(Bencode, PartialEq, Debug)]
+    #[bencode(crate = "self::renamed")]
+    struct Peers {
+        #[bencode(bytes)]
+        peers: Vec<SocketAddrV4>,
+        interval: u32,
+    }
+
+    #[test]
+    fn attributes() {
+        let node = Node {
+            id: [0xab; 20],
+            seed: true,
+            token: Some(*b"aoeu"),
+        };
+
+        let mut expected = b"d2:id20:".to_vec();
+        expected.extend_from_slice(&[0xab; 20]);
+        expected.extend_from_slice(b"4:seedi1e5:token4:aoeue");
+
+        assert_eq!(expected, to_vec(&node).unwrap());
+        assert_eq!(node, from_slice::<Node>(&expected).unwrap());
+
+        // Values & other serializers are supported as well.
+        let value = to_value(&node).unwrap();
+        assert_eq!(Some(1), value["seed"].as_int());
+        assert_eq!(node, from_value::<Node>(value).unwrap());
+    }
+
+    #[test]
+    fn optional_fields() {
+        let mut data = b"d2:id20:".to_vec();
+        data.extend_from_slice(&[0xab; 20]);
+        data.extend_from_slice(b"4:seedi0e7:unknownli1eee");
+
+        let node = from_slice::<Node>(&data).unwrap();
+        assert_eq!(None, node.token);
+        assert!(!node.seed);
+
+        // Unknown keys are skipped, missing optional fields are not written.
+        data.truncate(data.len() - 15);
+        data.push(b'e');
+        assert_eq!(data, to_vec(&node).unwrap());
+    }
+
+    #[test]
+    fn unknown_keys() {
+        // The unknown keys are kept as they are, including the unsorted keys
+        // of the nested dictionary.
+        let data = "d8:announce3:url7:comment4:test4:infod4:name1:a12:piece lengthi1e6:pieces2:xy\
+                    7:privatei1e6:sourced1:bi1e1:ai2eee7:versioni2ee";
+
+        let torrent = from_str::<Torrent>(data).unwrap();
+        assert_eq!(Some(true), torrent.info.private);
+        assert_eq!(b"xy".to_vec(), torrent.info.pieces);
+        assert_eq!(
+            b"d1:bi1e1:ai2ee",
+            torrent.info.extra.get("source").unwrap().as_bytes()
+        );
+        assert_eq!(Some(2), torrent.extra.get("version").unwrap().decode().ok());
+
+        assert_eq!(data, to_string(&torrent).unwrap());
+    }
+
+    #[test]
+    fn canonical_order() {
+        // The unknown keys sort before, between & after the fields, which
+        // are not declared in the canonical order.
+        let data = "d8:announce3:url7:comment4:test4:infod6:lengthi1e4:name1:a\
+                    12:piece lengthi1e6:pieces2:xy6:sourcei1ee7:versioni2ee";
+
+        let torrent = from_str::<Torrent>(data).unwrap();
+        assert_eq!(
+            vec![b"length".as_slice(), b"source"],
+            torrent
+                .info
+                .extra
+                .iter()
+                .map(|(k, _)| k.as_slice())
+                .collect::<Vec<_>>()
+        );
+
+        let unsorted = SerializerOptions {
+            sort_keys: false,
+            ..SerializerOptions::default()
+        };
+        assert_eq!(
+            data.as_bytes(),
+            to_vec_with_options(&torrent, unsorted).unwrap()
+        );
+    }
+
+    #[test]
+    fn crate_path() {
+        let peers = Peers {
+            peers: vec!["1.2.3.4:6881".parse().unwrap()],
+            interval: 60,
+        };
+        let data = b"d8:intervali60e5:peers6:\x01\x02\x03\x04\x1a\xe1e";
+
+        assert_eq!(data.to_vec(), to_vec(&peers).unwrap());
+        assert_eq!(peers, from_slice::<Peers>(data).unwrap());
+    }
+
+    #[test]
+    fn invalid_fields() {
+        let cases = [
+            // Missing field.
+            "d4:seedi1ee",
+            // Duplicate field.
+            "d2:id20:aaaaaaaaaaaaaaaaaaaa2:id20:aaaaaaaaaaaaaaaaaaaa4:seedi1ee",
+            // Byte string of a wrong length.
+            "d2:id3:abc4:seedi1ee",
+            // Integer, that is not a bool.
+            "d2:id20:aaaaaaaaaaaaaaaaaaaa4:seedi2ee",
+        ];
+
+        for data in cases {
+            assert!(from_str::<Node>(data).is_err(), "{}", data);
+        }
+
+        // Bools are read the same way as with `BoolEncoding::Integer`, so
+        // strings are accepted only outside of the strict mode.
+        let data = b"d2:id20:aaaaaaaaaaaaaaaaaaaa4:seed4:truee";
+        assert!(from_slice::<Node>(data).unwrap().seed);
+        assert!(matches!(
+            from_slice_strict::<Node>(data).map_err(Error::into_kind),
+            Err(ErrorKind::ExpectedInteger)
+        ));
+
+        assert!(matches!(
+            from_value::<Node>(Value::from(1)).map_err(Error::into_kind),
+            Err(ErrorKind::ExpectedDictionary)
+        ));
+    }
+}
@@ -0,0 +1,158 @@
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+    use std::path::Path;
+
+    use serde_derive::{Deserialize, Serialize};
+
+    use bitrust_bencode::{
+        from_slice, to_vec, DeserializerOptions, Document, Error, ErrorKind, RawValue,
+    };
+
+    #[derive(Deserialize, Serialize, PartialEq, Debug)]
+    struct File {
+        length: u64,
+        path: Vec<String>,
+    }
+
+    #[derive(Serialize)]
+    struct Info {
+        files: Vec<File>,
+        name: String,
+    }
+
+    #[derive(Serialize)]
+    struct Torrent {
+        announce: String,
+        info: Info,
+    }
+
+    fn multi_file_torrent(count: u64) -> Vec<u8> {
+        let files = (0..count)
+            .map(|i| File {
+                length: i * 1024,
+                path: vec![String::from("dir"), format!("file-{}", i)],
+            })
+            .collect();
+
+        to_vec(&Torrent {
+            announce: String::from("http://tracker"),
+            info: Info {
+                files,
+                name: String::from("many"),
+            },
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn navigation() {
+        let data = multi_file_torrent(1000);
+        let doc = Document::new(&data).unwrap();
+
+        assert_eq!(
+            Some("http://tracker"),
+            doc.get("announce").unwrap().as_str()
+        );
+
+        let files = doc.get(b"info").unwrap().get(b"files").unwrap();
+        assert!(files.is_list());
+        assert_eq!(1000, files.len());
+        assert_eq!(
+            Some(500 * 1024),
+            files.at(500).unwrap().get("length").unwrap().as_int()
+        );
+        assert_eq!(
+            File {
+                length: 999 * 1024,
+                path: vec![String::from("dir"), String::from("file-999")],
+            },
+            files.at(999).unwrap().decode::<File>().unwrap()
+        );
+
+        let total = files
+            .iter()
+            .filter_map(|file| file.get("length")?.as_int())
+            .sum::<i64>();
+        assert_eq!(1024 * 999 * 1000 / 2, total);
+
+        // Missing values.
+        assert!(doc.get("missing").is_none());
+        assert!(files.at(1000).is_none());
+        assert!(files.get("length").is_none());
+        assert!(doc.root().at(0).is_none());
+        assert!(files.at(0).unwrap().get("length").unwrap().is_empty());
+    }
+
+    #[test]
+    fn entries_and_raw_values() {
+        let data = b"d1:ai1e1:bl1:x1:yee";
+        let doc = Document::new(data).unwrap();
+
+        let keys = doc.root().entries().map(|(key, _)| key).collect::<Vec<_>>();
+        assert_eq!(vec![b"a".as_slice(), b"b"], keys);
+        assert_eq!(2, doc.root().len());
+
+        let list = doc.get("b").unwrap();
+        assert_eq!(b"l1:x1:ye", list.as_raw());
+        assert_eq!(
+            vec!["x", "y"],
+            list.iter().filter_map(|n| n.as_str()).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            RawValue::from_slice(b"l1:x1:ye").unwrap(),
+            list.to_raw_value()
+        );
+        assert!(list.entries().next().is_none());
+    }
+
+    #[test]
+    fn info_dictionary() {
+        let mut dir = env::current_dir().unwrap();
+        dir.push(Path::new(
+            "tests/data/ubuntu-19.10-desktop-amd64.iso.torrent",
+        ));
+        let f = fs::read(dir).unwrap();
+
+        #[derive(Deserialize)]
+        struct Metainfo<'a> {
+            #[serde(borrow)]
+            info: RawValue<'a>,
+        }
+
+        let doc = Document::new(&f).unwrap();
+        let info = doc.get("info").unwrap();
+
+        assert_eq!(
+            from_slice::<Metainfo>(&f).unwrap().info.as_bytes(),
+            info.as_raw()
+        );
+        assert_eq!(Some(1048576), info.get("piece length").unwrap().as_int());
+        assert_eq!(47000, info.get("pieces").unwrap().as_bytes().unwrap().len());
+    }
+
+    #[test]
+    fn invalid_documents() {
+        assert!(matches!(
+            Document::new(b"d1:ai1e").map_err(Error::into_kind),
+            Err(ErrorKind::EOF)
+        ));
+        assert!(matches!(
+            Document::new(b"i1ei2e").map_err(Error::into_kind),
+            Err(ErrorKind::TrailingCharacters)
+        ));
+        assert!(matches!(
+            Document::new(b"di1ei2ee").map_err(Error::into_kind),
+            Err(ErrorKind::ExpectedDictionaryKeyString)
+        ));
+
+        let options = DeserializerOptions {
+            strict: true,
+            ..DeserializerOptions::default()
+        };
+        let error = Document::with_options(b"d1:bi1e1:ai2ee", options).unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::UnsortedKeys));
+        assert_eq!(Some(7), error.position());
+    }
+}
@@ -0,0 +1,134 @@
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+    use std::path::Path;
+
+    use serde_derive::{Deserialize, Serialize};
+
+    use bitrust_bencode::{from_slice, to_vec, Document, Error, ErrorKind, Extra, RawValue, Value};
+
+    #[derive(Deserialize, Serialize, Debug)]
+    struct Info {
+        name: String,
+        #[serde(rename = "piece length")]
+        piece_length: u64,
+        #[serde(flatten)]
+        extra: Extra,
+    }
+
+    #[derive(Deserialize, Serialize, Debug)]
+    struct Torrent {
+        announce: String,
+        info: Info,
+        #[serde(flatten)]
+        extra: Extra,
+    }
+
+    #[test]
+    fn torrent_round_trip() {
+        let mut dir = env::current_dir().unwrap();
+        dir.push(Path::new(
+            "tests/data/ubuntu-19.10-desktop-amd64.iso.torrent",
+        ));
+        let f = fs::read(dir).unwrap();
+
+        let torrent = from_slice::<Torrent>(&f).unwrap();
+        assert_eq!(
+            Some(1571323134),
+            torrent
+                .extra
+                .get("creation date")
+                .unwrap()
+                .decode::<i64>()
+                .ok()
+        );
+        assert_eq!(
+            vec![b"length".as_slice(), b"pieces"],
+            torrent
+                .info
+                .extra
+                .iter()
+                .map(|(k, _)| k.as_slice())
+                .collect::<Vec<_>>()
+        );
+
+        assert_eq!(f, to_vec(&torrent).unwrap());
+    }
+
+    #[test]
+    fn vendor_keys() {
+        let input = b"d8:announce3:url4:infod4:name1:a12:piece lengthi2e6:source3:PTHe\
+                      7:similarl1:xe12:x_cross_seed2:id1:\xffd1:bi1e1:ai2eee";
+
+        let mut torrent = from_slice::<Torrent>(input).unwrap();
+        assert_eq!(
+            b"3:PTH",
+            torrent.info.extra.get("source").unwrap().as_bytes()
+        );
+        assert_eq!(3, torrent.extra.len());
+
+        // Nested dictionaries are kept in their original order.
+        assert_eq!(
+            b"d1:bi1e1:ai2ee",
+            torrent.extra.get(b"\xff").unwrap().as_bytes()
+        );
+
+        assert_eq!(input.to_vec(), to_vec(&torrent).unwrap());
+
+        torrent.info.extra.remove("source");
+        torrent
+            .info
+            .extra
+            .insert("private", RawValue::from_slice(b"i1e").unwrap());
+        assert_eq!(
+            Some(1),
+            from_slice::<Value>(&to_vec(&torrent.info).unwrap()).unwrap()["private"].as_int()
+        );
+    }
+
+    #[test]
+    fn info_hash() {
+        let input = b"d8:announce3:url4:infod4:name1:a12:piece lengthi2e\
+                      7:privatei1e6:sourced1:bi-5e1:al0:eeee";
+
+        // The raw bytes of the info dictionary are the ones hashed.
+        let torrent = from_slice::<Torrent>(input).unwrap();
+        let output = to_vec(&torrent).unwrap();
+        assert_eq!(
+            Document::new(input).unwrap().get("info").unwrap().as_raw(),
+            Document::new(&output)
+                .unwrap()
+                .get("info")
+                .unwrap()
+                .as_raw()
+        );
+
+        // Non-canonical values can not be written back the same way.
+        let input = b"d8:announce3:url4:infod4:name1:a12:piece lengthi2e\
+                      6:sourcei-0005eee";
+        assert!(matches!(
+            from_slice::<Torrent>(input).map_err(Error::into_kind),
+            Err(ErrorKind::NonCanonicalInteger)
+        ));
+
+        let input = b"d8:announce3:url4:infod4:name1:a12:piece lengthi2e\
+                      6:sourcel03:PTHeee";
+        assert!(matches!(
+            from_slice::<Torrent>(input).map_err(Error::into_kind),
+            Err(ErrorKind::NonCanonicalStringLength)
+        ));
+    }
+
+    #[test]
+    fn direct_use() {
+        let extra = from_slice::<Extra>(b"d1:ai01e1:bli1eee").unwrap();
+
+        // Without flattening, the values are borrowed exactly as they are.
+        assert_eq!(b"i01e", extra.get("a").unwrap().as_bytes());
+        assert_eq!(b"d1:ai01e1:bli1eee".to_vec(), to_vec(&extra).unwrap());
+
+        assert!(from_slice::<Extra>(b"li1ee").is_err());
+        assert!(from_slice::<Extra>(b"de").unwrap().is_empty());
+    }
+}
@@ -0,0 +1,131 @@
+#[cfg(all(test, feature = "json"))]
+mod tests {
+    use std::env;
+    use std::fs;
+    use std::path::Path;
+
+    use serde_json::json;
+
+    use bitrust_bencode::{
+        from_slice, from_str,
+        json::{from_json, to_json, to_json_with, BytesEncoding},
+        Value,
+    };
+
+    #[test]
+    fn scalars_and_containers() {
+        let value = from_str::<Value>("d3:bar4:spam3:fooli42ei-1eee").unwrap();
+        let json = value.to_json();
+
+        assert_eq!(json!({"bar": "spam", "foo": [42, -1]}), json);
+        assert_eq!(value, Value::from_json(&json).unwrap());
+    }
+
+    #[test]
+    fn byte_strings() {
+        let value = Value::from(vec![0xff, 0x00, 0x10]);
+
+        assert_eq!(json!({"$hex": "ff0010"}), value.to_json());
+        assert_eq!(
+            json!({"$base64": "/wAQ"}),
+            value.to_json_with(BytesEncoding::Base64)
+        );
+
+        assert_eq!(value, Value::from_json(&json!({"$hex": "ff0010"})).unwrap());
+        assert_eq!(
+            value,
+            Value::from_json(&json!({"$base64": "/wAQ"})).unwrap()
+        );
+    }
+
+    #[test]
+    fn escaped_dictionaries() {
+        // Keys, that are not valid UTF-8.
+        let value = from_str::<Value>("d1:ai1ee").unwrap();
+        let mut bytes = value.clone();
+        bytes
+            .as_dict_mut()
+            .unwrap()
+            .insert(vec![0xff], Value::from(2));
+
+        let json = bytes.to_json();
+        assert_eq!(json!({"$dict": [["a", 1], [{"$hex": "ff"}, 2]]}), json);
+        assert_eq!(bytes, Value::from_json(&json).unwrap());
+
+        // Dictionaries, that would be mistaken for tagged objects.
+        let value = from_str::<Value>("d4:$hex2:ffe").unwrap();
+        let json = value.to_json();
+        assert_eq!(json!({"$dict": [["$hex", "ff"]]}), json);
+        assert_eq!(value, Value::from_json(&json).unwrap());
+    }
+
+    #[test]
+    fn big_integers() {
+        let value = Value::from(u64::MAX);
+        assert_eq!(json!(u64::MAX), value.to_json());
+        assert_eq!(value, Value::from_json(&json!(u64::MAX)).unwrap());
+
+        let value = Value::Integer(i128::MIN);
+        let json = json!({"$int": "-170141183460469231731687303715884105728"});
+        assert_eq!(json, value.to_json());
+        assert_eq!(value, Value::from_json(&json).unwrap());
+    }
+
+    #[test]
+    fn unsupported_json() {
+        assert!(Value::from_json(&json!(1.5)).is_err());
+        assert!(Value::from_json(&json!(true)).is_err());
+        assert!(Value::from_json(&json!(null)).is_err());
+        assert!(Value::from_json(&json!({"$int": "1.5"})).is_err());
+        assert!(from_json(&json!({"$int": "--1"})).is_err());
+        assert!(from_json(&json!({"$dict": [[1, 2]]})).is_err());
+        assert!(to_json(b"i1ei2e").is_err());
+        assert!(Value::from_json(&json!({"$hex": "xyz"})).is_err());
+        assert!(Value::from_json(&json!({"$dict": [["a"]]})).is_err());
+    }
+
+    #[test]
+    fn torrent_round_trip() {
+        let mut dir = env::current_dir().unwrap();
+        dir.push(Path::new(
+            "tests/data/ubuntu-19.10-desktop-amd64.iso.torrent",
+        ));
+        let f = fs::read(dir).unwrap();
+
+        let json = to_json(&f).unwrap();
+        assert_eq!("ubuntu-19.10-desktop-amd64.iso", json["info"]["name"]);
+        assert!(json["info"]["pieces"]["$hex"].is_string());
+
+        assert_eq!(from_slice::<Value>(&f).unwrap().to_json(), json);
+        assert_eq!(f, from_json(&json).unwrap());
+    }
+
+    #[test]
+    fn non_canonical_round_trip() {
+        // Unsorted & duplicate keys are kept in their order.
+        let data = b"d1:bi1e1:ai2e1:ai3ee";
+        let json = to_json(data).unwrap();
+        assert_eq!(json!({"$dict": [["b", 1], ["a", 2], ["a", 3]]}), json);
+        assert_eq!(data.as_slice(), from_json(&json).unwrap());
+
+        // Integers beyond `i64` & `u64`.
+        let data =
+            b"li9223372036854775808ei-18446744073709551616ei123456789012345678901234567890ee";
+        let json = to_json(data).unwrap();
+        assert_eq!(
+            json!([
+                9223372036854775808u64,
+                {"$int": "-18446744073709551616"},
+                {"$int": "123456789012345678901234567890"},
+            ]),
+            json
+        );
+        assert_eq!(data.as_slice(), from_json(&json).unwrap());
+
+        // Byte strings, that are not valid UTF-8, with the other encoding.
+        assert_eq!(
+            json!({"a": {"$base64": "/w=="}}),
+            to_json_with(b"d1:a1:\xffe", BytesEncoding::Base64).unwrap()
+        );
+    }
+}
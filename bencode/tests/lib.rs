@@ -5,7 +5,7 @@ mod tests {
     use arbitrary::{Arbitrary, Unstructured};
     use quickcheck_macros::quickcheck;
 
-    use bitrust_bencode::{from_str, to_string};
+    use bitrust_bencode::{from_str, to_string, Error, ErrorKind};
     use rand::Rng;
     use serde_derive::{Deserialize, Serialize};
 
@@ -74,4 +74,118 @@ mod tests {
             from_str::<Value>(&to_string(&value).unwrap()).unwrap()
         );
     }
+
+    #[derive(Deserialize, Serialize, Eq, PartialEq, Debug)]
+    enum Message {
+        Ping,
+
+        Piece(u32),
+
+        Request(u32, u32, u32),
+
+        Bitfield { pieces: Vec<u8>, complete: bool },
+    }
+
+    #[test]
+    fn enum_variants() {
+        let cases = [
+            (Message::Ping, "4:Ping"),
+            (Message::Piece(7), "d5:Piecei7ee"),
+            (Message::Request(1, 2, 3), "d7:Requestli1ei2ei3eee"),
+            (
+                Message::Bitfield {
+                    pieces: vec![1, 2],
+                    complete: false,
+                },
+                "d8:Bitfieldd8:complete5:false6:piecesli1ei2eeee",
+            ),
+        ];
+
+        for (message, encoded) in cases {
+            assert_eq!(encoded, to_string(&message).unwrap());
+            assert_eq!(message, from_str::<Message>(encoded).unwrap());
+        }
+
+        assert_eq!(
+            vec![Message::Ping, Message::Piece(1)],
+            from_str::<Vec<Message>>("l4:Pingd5:Piecei1eee").unwrap()
+        );
+    }
+
+    #[test]
+    fn invalid_enums() {
+        assert!(matches!(
+            from_str::<Message>("i1e").map_err(Error::into_kind),
+            Err(ErrorKind::ExpectedEnum)
+        ));
+        assert!(from_str::<Message>("4:Pong").is_err());
+        assert!(from_str::<Message>("d4:Pingi1ee").is_err());
+        assert!(matches!(
+            from_str::<Message>("d5:Piecei1e4:Pingi1ee").map_err(Error::into_kind),
+            Err(ErrorKind::ExpectedDictionaryEnd)
+        ));
+
+        let err = from_str::<Message>("d5:Piece4:spame").unwrap_err();
+        assert_eq!(Some("Piece"), err.path());
+    }
+
+    #[quickcheck]
+    fn enum_circular_property(piece: u32, pieces: Vec<u8>, complete: bool) {
+        for message in [
+            Message::Piece(piece),
+            Message::Request(piece, piece / 2, piece / 3),
+            Message::Bitfield { pieces, complete },
+        ] {
+            assert_eq!(
+                message,
+                from_str::<Message>(&to_string(&message).unwrap()).unwrap()
+            );
+        }
+    }
+
+    #[derive(Deserialize, Serialize, Eq, PartialEq, Debug)]
+    struct Announce {
+        info_hash: String,
+
+        #[serde(default)]
+        numwant: Option<u32>,
+
+        tracker_id: Option<String>,
+
+        peers: HashMap<String, Option<u16>>,
+    }
+
+    #[test]
+    fn optional_fields() {
+        let announce = Announce {
+            info_hash: String::from("hash"),
+            numwant: Some(50),
+            tracker_id: None,
+            peers: HashMap::from([(String::from("a"), Some(1)), (String::from("b"), None)]),
+        };
+
+        let encoded = to_string(&announce).unwrap();
+        assert_eq!("d9:info_hash4:hash7:numwanti50e5:peersd1:ai1eee", encoded);
+
+        let decoded = from_str::<Announce>(&encoded).unwrap();
+        assert_eq!(announce.numwant, decoded.numwant);
+        assert_eq!(None, decoded.tracker_id);
+        assert_eq!(Some(&Some(1)), decoded.peers.get("a"));
+        assert_eq!(None, decoded.peers.get("b"));
+    }
+
+    #[quickcheck]
+    fn option_circular_property(numwant: Option<u32>, tracker_id: Option<String>) {
+        let announce = Announce {
+            info_hash: String::from("hash"),
+            numwant,
+            tracker_id,
+            peers: HashMap::new(),
+        };
+
+        assert_eq!(
+            announce,
+            from_str::<Announce>(&to_string(&announce).unwrap()).unwrap()
+        );
+    }
 }
@@ -0,0 +1,67 @@
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use bitrust_bencode::{bencode, to_vec, Value};
+
+    #[test]
+    fn scalars() {
+        assert_eq!(Value::Integer(-3), bencode!(-3));
+        assert_eq!(Value::Integer(42), bencode!(40 + 2));
+        assert_eq!(Value::Integer(u64::MAX.into()), bencode!(u64::MAX));
+        assert_eq!(Value::Integer(3), bencode!(vec![1, 2, 3].len()));
+        assert_eq!(Value::from("spam"), bencode!("spam"));
+        assert_eq!(Value::Bytes(vec![0, 255]), bencode!(b"\x00\xff"));
+        let node_id = [7u8; 20];
+        assert_eq!(Value::Bytes(vec![7; 20]), bencode!(node_id));
+        assert_eq!(Value::List(vec![]), bencode!([]));
+        assert_eq!(Value::Dict(BTreeMap::new()), bencode!({}));
+    }
+
+    #[test]
+    fn krpc_query() {
+        let node_id = [0xabu8; 20];
+        let target = String::from("mnopqrstuvwxyz123456");
+
+        let query = bencode! {
+            "t" => b"aa",
+            "y" => "q",
+            "q" => "find_node",
+            "a" => {
+                "id" => node_id,
+                "target" => target.as_str(),
+            },
+        };
+
+        let mut expected = b"d1:ad2:id20:".to_vec();
+        expected.extend_from_slice(&node_id);
+        expected.extend_from_slice(b"6:target20:mnopqrstuvwxyz123456e1:q9:find_node1:t2:aa1:y1:qe");
+
+        assert_eq!(expected, to_vec(&query).unwrap());
+        assert_eq!(Some("find_node"), query["q"].as_str());
+    }
+
+    #[test]
+    fn nested_values() {
+        let peer = bencode!({ "ip" => "10.0.0.1", "port" => 6881 });
+        let key = b"k".to_vec();
+
+        let value = bencode!([
+            1,
+            [2, [3], []],
+            { "peer" => peer.clone(), key => [{}], },
+            "end",
+        ]);
+
+        assert_eq!(
+            b"li1eli2eli3eeleed1:kldee4:peerd2:ip8:10.0.0.14:porti6881eee3:ende".to_vec(),
+            to_vec(&value).unwrap()
+        );
+        assert_eq!(&peer, &value[2]["peer"]);
+    }
+
+    #[test]
+    fn duplicate_keys() {
+        assert_eq!(bencode!({ "a" => 2 }), bencode!({ "a" => 1, "a" => 2 }));
+    }
+}
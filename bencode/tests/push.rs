@@ -0,0 +1,184 @@
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+    use std::path::Path;
+
+    use serde_derive::Deserialize;
+
+    use bitrust_bencode::{
+        value_length, value_length_with_options, DeserializerOptions, Error, ErrorKind, PushParser,
+        Status, Value,
+    };
+
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct Metadata {
+        msg_type: u8,
+        piece: u32,
+        total_size: u32,
+    }
+
+    #[test]
+    fn value_lengths() {
+        assert_eq!(Status::Complete(4), value_length(b"i42e").unwrap());
+        assert_eq!(Status::Complete(6), value_length(b"4:spamrest").unwrap());
+        assert_eq!(Status::Complete(2), value_length(b"lei1e").unwrap());
+        assert_eq!(
+            Status::Complete(20),
+            value_length(b"d3:fooli1e1:ae1:bdee").unwrap()
+        );
+
+        assert_eq!(Status::NeedMore(1), value_length(b"").unwrap());
+        assert_eq!(Status::NeedMore(1), value_length(b"i42").unwrap());
+        assert_eq!(Status::NeedMore(1), value_length(b"10").unwrap());
+        assert_eq!(Status::NeedMore(7), value_length(b"10:foo").unwrap());
+        assert_eq!(Status::NeedMore(1), value_length(b"d3:foo").unwrap());
+
+        assert!(matches!(
+            value_length(b"di1ei2ee").map_err(Error::into_kind),
+            Err(ErrorKind::ExpectedDictionaryKeyString)
+        ));
+        assert!(matches!(
+            value_length(b"i1xe").map_err(Error::into_kind),
+            Err(ErrorKind::ExpectedIntegerEnd)
+        ));
+        for data in [&b"i-e"[..], b"i--1e", b"i1-e", b"li-ee"] {
+            assert!(matches!(
+                value_length(data).map_err(Error::into_kind),
+                Err(ErrorKind::ExpectedSignedNumber)
+            ));
+        }
+        // Integers, that are not decoded, are not limited in size.
+        assert_eq!(
+            Status::Complete(43),
+            value_length(b"i-1000000000000000000000000000000000000000e").unwrap()
+        );
+        assert!(matches!(
+            value_length(b"3x").map_err(Error::into_kind),
+            Err(ErrorKind::ExpectedStringIntegerLength)
+        ));
+
+        let error = value_length(b"l1:ax").unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::UnknownType));
+        assert_eq!(Some(4), error.position());
+
+        // The length is claimed by the input.
+        assert_eq!(
+            Status::NeedMore(usize::MAX),
+            value_length(b"18446744073709551615:").unwrap()
+        );
+
+        let options = DeserializerOptions {
+            max_input_size: 1024,
+            ..DeserializerOptions::default()
+        };
+        assert_eq!(
+            Status::Complete(4),
+            value_length_with_options(b"i42e", options).unwrap()
+        );
+        assert!(matches!(
+            value_length_with_options(b"18446744073709551615:", options).map_err(Error::into_kind),
+            Err(ErrorKind::InputTooLarge)
+        ));
+    }
+
+    #[test]
+    fn ut_metadata_message() {
+        // A data message is a dictionary followed by a piece of the metadata.
+        let message = b"d8:msg_typei1e5:piecei0e10:total_sizei8eeinfo-ish";
+        let mut parser = PushParser::new();
+
+        let mut status = Status::NeedMore(1);
+        for chunk in message.chunks(3) {
+            status = parser.feed(chunk).unwrap();
+        }
+
+        assert_eq!(Status::Complete(message.len() - 8), status);
+        assert_eq!(&message[..message.len() - 8], parser.value().unwrap());
+        assert_eq!(b"info-ish", parser.remaining());
+        assert_eq!(
+            Metadata {
+                msg_type: 1,
+                piece: 0,
+                total_size: 8,
+            },
+            parser.decode::<Metadata>().unwrap()
+        );
+    }
+
+    #[test]
+    fn byte_by_byte() {
+        let mut dir = env::current_dir().unwrap();
+        dir.push(Path::new(
+            "tests/data/ubuntu-19.10-desktop-amd64.iso.torrent",
+        ));
+        let f = fs::read(dir).unwrap();
+
+        let mut parser = PushParser::new();
+
+        for (i, byte) in f.iter().enumerate() {
+            let status = parser.feed(&[*byte]).unwrap();
+
+            if i + 1 < f.len() {
+                assert!(matches!(status, Status::NeedMore(_)));
+            } else {
+                assert_eq!(Status::Complete(f.len()), status);
+            }
+        }
+
+        assert_eq!(
+            Some("ubuntu-19.10-desktop-amd64.iso"),
+            parser.decode::<Value>().unwrap()["info"]["name"].as_str()
+        );
+    }
+
+    #[test]
+    fn consecutive_values() {
+        let mut parser = PushParser::new();
+
+        assert!(matches!(
+            parser.decode::<Value>().map_err(Error::into_kind),
+            Err(ErrorKind::EOF)
+        ));
+
+        assert_eq!(Status::NeedMore(2), parser.feed(b"l4:sp").unwrap());
+        assert_eq!(Status::Complete(8), parser.feed(b"amei7e4:").unwrap());
+        assert_eq!(Some("spam"), parser.decode::<Value>().unwrap()[0].as_str());
+
+        assert_eq!(Status::Complete(3), parser.reset().unwrap());
+        assert_eq!(7, parser.decode::<i64>().unwrap());
+
+        assert_eq!(Status::NeedMore(4), parser.reset().unwrap());
+        assert_eq!(Status::Complete(6), parser.feed(b"spam").unwrap());
+        assert_eq!("spam", parser.decode::<String>().unwrap());
+    }
+
+    #[test]
+    fn limits() {
+        let options = DeserializerOptions {
+            max_depth: 2,
+            max_string_length: 4,
+            max_input_size: 12,
+            ..DeserializerOptions::default()
+        };
+
+        let mut parser = PushParser::with_options(options);
+        assert!(matches!(
+            parser.feed(b"lll").map_err(Error::into_kind),
+            Err(ErrorKind::DepthLimitExceeded)
+        ));
+
+        let mut parser = PushParser::with_options(options);
+        assert!(matches!(
+            parser.feed(b"5:").map_err(Error::into_kind),
+            Err(ErrorKind::StringTooLong)
+        ));
+
+        // The size of the value is known before its bytes arrive.
+        let mut parser = PushParser::with_options(options);
+        assert!(matches!(
+            parser.feed(b"l4:spam4:").map_err(Error::into_kind),
+            Err(ErrorKind::InputTooLarge)
+        ));
+    }
+}
@@ -0,0 +1,103 @@
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+    use std::path::Path;
+
+    use serde_derive::{Deserialize, Serialize};
+
+    use bitrust_bencode::{
+        from_slice, from_str, to_string, to_vec, Error, ErrorKind, RawValue, Value,
+    };
+
+    fn ubuntu_torrent() -> Vec<u8> {
+        let mut dir = env::current_dir().unwrap();
+        dir.push(Path::new(
+            "tests/data/ubuntu-19.10-desktop-amd64.iso.torrent",
+        ));
+
+        fs::read(dir).unwrap()
+    }
+
+    #[test]
+    fn raw_values() {
+        for input in ["i-42e", "4:spam", "le", "li1e4:spame", "d3:fooli1eee"] {
+            assert_eq!(
+                input.as_bytes(),
+                from_str::<RawValue>(input).unwrap().as_bytes()
+            );
+        }
+
+        assert!(matches!(
+            RawValue::from_slice(b"i1ei2e").map_err(Error::into_kind),
+            Err(ErrorKind::TrailingCharacters)
+        ));
+        assert!(matches!(
+            RawValue::from_slice(b"li1e").map_err(Error::into_kind),
+            Err(ErrorKind::EOF)
+        ));
+        assert!(matches!(
+            RawValue::from_slice(b"di1ei2ee").map_err(Error::into_kind),
+            Err(ErrorKind::ExpectedDictionaryKeyString)
+        ));
+        assert!(matches!(
+            RawValue::from_slice(b"x").map_err(Error::into_kind),
+            Err(ErrorKind::UnknownType)
+        ));
+    }
+
+    #[test]
+    fn raw_fields() {
+        #[derive(Deserialize, Serialize, Debug)]
+        struct Message<'a> {
+            #[serde(borrow)]
+            payload: RawValue<'a>,
+            t: &'a str,
+        }
+
+        let input = "d7:payloadd1:ai1e1:bli2eee1:t2:aae";
+        let message = from_str::<Message>(input).unwrap();
+
+        assert_eq!(b"d1:ai1e1:bli2eee", message.payload.as_bytes());
+        assert_eq!(
+            Some(2),
+            message.payload.decode::<Value>().unwrap()["b"][0].as_int()
+        );
+
+        // Raw values are written back as they are.
+        assert_eq!(input, to_string(&message).unwrap());
+    }
+
+    #[test]
+    fn info_dictionary_span() {
+        #[derive(Deserialize, Debug)]
+        struct TorrentInfo<'a> {
+            name: &'a str,
+        }
+
+        #[derive(Deserialize, Debug)]
+        struct TorrentMetainfo<'a> {
+            #[serde(borrow)]
+            info: RawValue<'a>,
+        }
+
+        let f = ubuntu_torrent();
+        let metainfo = from_slice::<TorrentMetainfo>(&f).unwrap();
+
+        // The info dictionary is the last value of the top-level dictionary.
+        let start = f.windows(6).position(|w| w == b"4:info").unwrap() + 6;
+        assert_eq!(&f[start..f.len() - 1], metainfo.info.as_bytes());
+
+        assert_eq!(
+            "ubuntu-19.10-desktop-amd64.iso",
+            metainfo.info.decode::<TorrentInfo>().unwrap().name
+        );
+
+        // Re-encoding the decoded dictionary yields the same bytes for
+        // canonical input.
+        assert_eq!(
+            metainfo.info.as_bytes(),
+            to_vec(&metainfo.info.decode::<Value>().unwrap()).unwrap()
+        );
+    }
+}
@@ -4,7 +4,10 @@ mod tests {
     use quickcheck_macros::quickcheck;
     use serde_derive::Serialize;
 
-    use bitrust_bencode::{to_string, to_vec};
+    use bitrust_bencode::{
+        to_string, to_vec, to_vec_with_options, to_writer, BoolEncoding, Error, ErrorKind,
+        FloatEncoding, Serializer, SerializerOptions,
+    };
 
     macro_rules! integer_test {
         ($method: ident, $type:ty) => {
@@ -28,6 +31,7 @@ mod tests {
     integer_test!(isize_integers, isize);
 
     #[test]
+    #[allow(clippy::legacy_numeric_constants)]
     fn integers_near_bounds() {
         assert_eq!(
             format!("i{}e", std::u8::MAX),
@@ -68,6 +72,12 @@ mod tests {
             format!("i{}e", std::i64::MAX),
             to_string(&std::i64::MAX).unwrap()
         );
+
+        assert_eq!(format!("i{}e", i64::MIN), to_string(&i64::MIN).unwrap());
+
+        assert_eq!(format!("i{}e", u128::MAX), to_string(&u128::MAX).unwrap());
+
+        assert_eq!(format!("i{}e", i128::MIN), to_string(&i128::MIN).unwrap());
     }
 
     #[test]
@@ -100,6 +110,7 @@ mod tests {
     float_test!(f64_float, f64);
 
     #[quickcheck]
+    #[allow(clippy::needless_borrow)]
     fn bytes(value: String) {
         assert_eq!(
             format!("{}:{}", value.len(), value).as_bytes(),
@@ -135,7 +146,7 @@ mod tests {
         }
 
         assert_eq!(
-            r#"d6:string10:somestring7:stringsl1:a1:b1:ce12:string_slice100:longstringlongstringlongstringlongstringlongstringlongstringlongstringlongstringlongstringlongstring13:string_slicesl1:d1:e1:f1:gee"#,
+            r#"d6:string10:somestring12:string_slice100:longstringlongstringlongstringlongstringlongstringlongstringlongstringlongstringlongstringlongstring13:string_slicesl1:d1:e1:f1:ge7:stringsl1:a1:b1:cee"#,
             to_string(&StringTest {
                 string: String::from("somestring"),
                 strings: vec!(String::from("a"), String::from("b"), String::from("c")),
@@ -160,7 +171,7 @@ mod tests {
         }
 
         assert_eq!(
-            r#"d7:integeri3000e16:negative_integeri-89343451e12:inner_structd6:string4:asdfee"#,
+            r#"d12:inner_structd6:string4:asdfe7:integeri3000e16:negative_integeri-89343451ee"#,
             to_string(&MixedStructTest {
                 integer: 3000,
                 negative_integer: -89343451,
@@ -169,4 +180,308 @@ mod tests {
             .unwrap()
         );
     }
+
+    #[test]
+    fn writers() {
+        let mut data = Vec::new();
+        to_writer(&mut data, &vec!["a", "b"]).unwrap();
+        assert_eq!(b"l1:a1:be", data.as_slice());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn io_writers() {
+        let mut ser = Serializer::new(std::io::Cursor::new(Vec::new()));
+        serde::Serialize::serialize(&-5, &mut ser).unwrap();
+        assert_eq!(b"i-5e", ser.into_inner().into_inner().as_slice());
+
+        struct FailingWriter;
+
+        impl std::io::Write for FailingWriter {
+            fn write(&mut self, _buf: &[u8]) -> std::io::Result<usize> {
+                Err(std::io::Error::other("closed"))
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        assert!(matches!(
+            to_writer(FailingWriter, &1).map_err(Error::into_kind),
+            Err(ErrorKind::IO(_))
+        ));
+    }
+
+    #[test]
+    fn canonical_key_order() {
+        use std::collections::{BTreeMap, HashMap};
+
+        #[derive(Serialize)]
+        struct Unsorted {
+            zebra: u8,
+            apple: u8,
+            #[serde(rename = "piece length")]
+            piece_length: u8,
+            pieces: u8,
+        }
+
+        let value = Unsorted {
+            zebra: 1,
+            apple: 2,
+            piece_length: 3,
+            pieces: 4,
+        };
+
+        assert_eq!(
+            "d5:applei2e12:piece lengthi3e6:piecesi4e5:zebrai1ee",
+            to_string(&value).unwrap()
+        );
+
+        // Unsorted serializer keeps the order of the fields.
+        let mut ser = Serializer::unsorted(Vec::new());
+        serde::Serialize::serialize(&value, &mut ser).unwrap();
+        assert_eq!(
+            b"d5:zebrai1e5:applei2e12:piece lengthi3e6:piecesi4ee".as_slice(),
+            ser.into_inner().as_slice()
+        );
+
+        // Keys are compared as raw bytes, not by their encoded form.
+        let map = HashMap::from([("aa", 1), ("b", 2), ("a", 3), ("\u{e9}", 4), ("Z", 5)]);
+        assert_eq!(
+            "d1:Zi5e1:ai3e2:aai1e1:bi2e2:\u{e9}i4ee",
+            to_string(&map).unwrap()
+        );
+
+        // Nested dictionaries are sorted as well.
+        let nested = BTreeMap::from([("outer", HashMap::from([("y", 1), ("x", 2)]))]);
+        assert_eq!("d5:outerd1:xi2e1:yi1eee", to_string(&nested).unwrap());
+    }
+
+    #[test]
+    fn duplicate_keys() {
+        #[derive(Serialize)]
+        struct Duplicate {
+            a: u8,
+            #[serde(rename = "a")]
+            b: u8,
+        }
+
+        assert!(matches!(
+            to_string(&Duplicate { a: 1, b: 2 }).map_err(Error::into_kind),
+            Err(ErrorKind::DuplicateKey)
+        ));
+
+        // Duplicates are rejected even without sorting.
+        let mut ser = Serializer::unsorted(Vec::new());
+        assert!(matches!(
+            serde::Serialize::serialize(&Duplicate { a: 1, b: 2 }, &mut ser)
+                .map_err(Error::into_kind),
+            Err(ErrorKind::DuplicateKey)
+        ));
+
+        let mut ser = Serializer::unsorted(Vec::new());
+        let entries = [("a", 1), ("b", 2), ("a", 3)];
+        assert!(matches!(
+            serde::Serializer::collect_map(&mut ser, entries).map_err(Error::into_kind),
+            Err(ErrorKind::DuplicateKey)
+        ));
+    }
+
+    #[test]
+    fn map_keys() {
+        use std::collections::BTreeMap;
+
+        // Integer keys are converted into strings & sorted as such.
+        let map = BTreeMap::from([(1u32, "a"), (2, "b"), (10, "c")]);
+        assert_eq!("d1:11:a2:101:c1:21:be", to_string(&map).unwrap());
+
+        let map = BTreeMap::from([('x', 1), ('y', 2)]);
+        assert_eq!("d1:xi1e1:yi2ee", to_string(&map).unwrap());
+
+        let map = BTreeMap::from([(serde_bytes::ByteBuf::from(vec![0xff, 0x00]), 1)]);
+        assert_eq!(b"d2:\xff\x00i1ee".as_slice(), to_vec(&map).unwrap());
+
+        #[derive(Serialize, PartialEq, Eq, PartialOrd, Ord)]
+        enum Kind {
+            Peers,
+        }
+
+        let map = BTreeMap::from([(Kind::Peers, 1)]);
+        assert_eq!("d5:Peersi1ee", to_string(&map).unwrap());
+
+        // Other types are rejected.
+        assert!(matches!(
+            to_string(&BTreeMap::from([((1, 2), 1)])).map_err(Error::into_kind),
+            Err(ErrorKind::KeyMustBeAString)
+        ));
+        assert!(matches!(
+            to_string(&BTreeMap::from([(true, 1)])).map_err(Error::into_kind),
+            Err(ErrorKind::KeyMustBeAString)
+        ));
+        assert!(matches!(
+            to_string(&BTreeMap::from([(vec![1u8], 1)])).map_err(Error::into_kind),
+            Err(ErrorKind::KeyMustBeAString)
+        ));
+        assert!(matches!(
+            to_string(&BTreeMap::from([(Some("a"), 1)])).map_err(Error::into_kind),
+            Err(ErrorKind::KeyMustBeAString)
+        ));
+    }
+
+    #[test]
+    fn options() {
+        use std::collections::BTreeMap;
+
+        use serde::Serialize as _;
+
+        #[derive(Serialize)]
+        struct Peer<'a> {
+            id: Option<&'a str>,
+            port: Option<u16>,
+        }
+
+        let peer = Peer {
+            id: None,
+            port: Some(6881),
+        };
+        assert_eq!("d4:porti6881ee", to_string(&peer).unwrap());
+
+        // The entries with a `None` value are skipped in both modes.
+        let map = BTreeMap::from([("a", None), ("b", Some(1)), ("c", None)]);
+        assert_eq!("d1:bi1ee", to_string(&map).unwrap());
+
+        let mut ser = Serializer::unsorted(Vec::new());
+        map.serialize(&mut ser).unwrap();
+        assert_eq!(b"d1:bi1ee".as_bytes(), ser.into_inner());
+
+        // There is no representation of `None` elsewhere.
+        assert!(matches!(
+            to_string(&vec![Some(1), None]).map_err(Error::into_kind),
+            Err(ErrorKind::UnsupportedNone)
+        ));
+        assert!(matches!(
+            to_string(&(1, None::<u8>)).map_err(Error::into_kind),
+            Err(ErrorKind::UnsupportedNone)
+        ));
+        assert!(matches!(
+            to_string(&None::<u8>).map_err(Error::into_kind),
+            Err(ErrorKind::UnsupportedNone)
+        ));
+    }
+
+    #[test]
+    fn units() {
+        use std::collections::BTreeMap;
+
+        #[derive(Serialize)]
+        struct Marker;
+
+        #[derive(Serialize)]
+        struct Message {
+            a: (),
+            b: u8,
+            c: Marker,
+        }
+
+        // The entries with a unit value are skipped the same way as `None`.
+        assert_eq!("de", to_string(&BTreeMap::from([("a", ())])).unwrap());
+        assert_eq!(
+            "d1:bi1ee",
+            to_string(&Message {
+                a: (),
+                b: 1,
+                c: Marker
+            })
+            .unwrap()
+        );
+
+        let mut ser = Serializer::unsorted(Vec::new());
+        serde::Serialize::serialize(&BTreeMap::from([("a", ()), ("b", ())]), &mut ser).unwrap();
+        assert_eq!(b"de".as_bytes(), ser.into_inner());
+
+        // There is no representation of a unit elsewhere.
+        assert!(matches!(
+            to_string(&vec![()]).map_err(Error::into_kind),
+            Err(ErrorKind::UnsupportedUnit)
+        ));
+        assert!(matches!(
+            to_string(&Marker).map_err(Error::into_kind),
+            Err(ErrorKind::UnsupportedUnit)
+        ));
+        assert!(matches!(
+            to_string(&Some(())).map_err(Error::into_kind),
+            Err(ErrorKind::UnsupportedUnit)
+        ));
+    }
+
+    #[test]
+    fn encodings() {
+        #[derive(Serialize)]
+        struct Handshake {
+            upload_only: bool,
+            ratio: f32,
+        }
+
+        let handshake = Handshake {
+            upload_only: true,
+            ratio: 1.5,
+        };
+        assert_eq!(
+            "d5:ratio3:1.511:upload_only4:truee",
+            to_string(&handshake).unwrap()
+        );
+
+        let options = SerializerOptions {
+            bools: BoolEncoding::Integer,
+            floats: FloatEncoding::FixedPoint(3),
+            ..SerializerOptions::default()
+        };
+        assert_eq!(
+            b"d5:ratioi1500e11:upload_onlyi1ee".as_bytes(),
+            to_vec_with_options(&handshake, options).unwrap()
+        );
+        assert_eq!(
+            b"i0e".as_bytes(),
+            to_vec_with_options(&false, options).unwrap()
+        );
+        assert_eq!(
+            b"i-3e".as_bytes(),
+            to_vec_with_options(&-0.0025, options).unwrap()
+        );
+        assert!(matches!(
+            to_vec_with_options(&f64::NAN, options).map_err(Error::into_kind),
+            Err(ErrorKind::UnsupportedFloat)
+        ));
+        assert!(matches!(
+            to_vec_with_options(&1e300, options).map_err(Error::into_kind),
+            Err(ErrorKind::UnsupportedFloat)
+        ));
+
+        // The number of decimal places is out of the range of the scale.
+        for decimals in [400, u32::MAX] {
+            let options = SerializerOptions {
+                floats: FloatEncoding::FixedPoint(decimals),
+                ..SerializerOptions::default()
+            };
+            assert!(matches!(
+                to_vec_with_options(&1.5, options).map_err(Error::into_kind),
+                Err(ErrorKind::UnsupportedFloat)
+            ));
+        }
+
+        let options = SerializerOptions {
+            bools: BoolEncoding::Error,
+            floats: FloatEncoding::Error,
+            ..SerializerOptions::default()
+        };
+        assert!(matches!(
+            to_vec_with_options(&true, options).map_err(Error::into_kind),
+            Err(ErrorKind::UnsupportedBool)
+        ));
+        assert!(matches!(
+            to_vec_with_options(&1.5, options).map_err(Error::into_kind),
+            Err(ErrorKind::UnsupportedFloat)
+        ));
+    }
 }
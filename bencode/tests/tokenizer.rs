@@ -0,0 +1,120 @@
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+    use std::path::Path;
+
+    use bitrust_bencode::{from_slice, Error, ErrorKind, Event, Tokenizer, Value};
+
+    fn events(input: &[u8]) -> Result<Vec<(usize, Event<'_>)>, Error> {
+        Tokenizer::new(input).collect()
+    }
+
+    #[test]
+    fn events_with_offsets() {
+        assert_eq!(
+            vec![
+                (0, Event::DictStart),
+                (1, Event::Bytes(b"foo")),
+                (6, Event::ListStart),
+                (7, Event::IntStart),
+                (8, Event::Integer(-42)),
+                (11, Event::End),
+                (12, Event::Bytes(b"")),
+                (14, Event::End),
+                (15, Event::End),
+            ],
+            events(b"d3:fooli-42e0:ee").unwrap()
+        );
+
+        assert_eq!(vec![(0, Event::Bytes(b"spam"))], events(b"4:spam").unwrap());
+        assert_eq!(
+            vec![(0, Event::ListStart), (1, Event::End)],
+            events(b"le").unwrap()
+        );
+    }
+
+    fn first_error(input: &[u8]) -> Error {
+        let mut tokenizer = Tokenizer::new(input);
+        let error = tokenizer.find_map(Result::err).unwrap();
+
+        // The iteration stops after an error.
+        assert!(tokenizer.next().is_none());
+
+        error
+    }
+
+    #[test]
+    fn invalid_input() {
+        let error = first_error(b"");
+        assert!(matches!(error.kind(), ErrorKind::EOF));
+        assert_eq!(Some(0), error.position());
+
+        let error = first_error(b"li1e");
+        assert!(matches!(error.kind(), ErrorKind::EOF));
+        assert_eq!(Some(4), error.position());
+
+        let error = first_error(b"i1x");
+        assert!(matches!(error.kind(), ErrorKind::ExpectedIntegerEnd));
+        assert_eq!(Some(2), error.position());
+
+        let error = first_error(b"ie");
+        assert!(matches!(error.kind(), ErrorKind::ExpectedSignedNumber));
+        assert_eq!(Some(1), error.position());
+
+        let error = first_error(b"di1ei2ee");
+        assert!(matches!(
+            error.kind(),
+            ErrorKind::ExpectedDictionaryKeyString
+        ));
+        assert_eq!(Some(1), error.position());
+
+        let error = first_error(b"d3:fooe");
+        assert!(matches!(error.kind(), ErrorKind::UnknownType));
+        assert_eq!(Some(6), error.position());
+
+        let error = first_error(b"i1ei2e");
+        assert!(matches!(error.kind(), ErrorKind::TrailingCharacters));
+        assert_eq!(Some(3), error.position());
+    }
+
+    #[test]
+    fn scan_torrent() {
+        let mut dir = env::current_dir().unwrap();
+        dir.push(Path::new(
+            "tests/data/ubuntu-19.10-desktop-amd64.iso.torrent",
+        ));
+        let f = fs::read(dir).unwrap();
+
+        // Locate the info dictionary & count its pieces without decoding
+        // the whole torrent, relying on the keys being followed by values.
+        let mut tokenizer = Tokenizer::new(&f);
+        let mut previous = None;
+        let mut info = None;
+        let mut pieces = None;
+
+        while let Some(result) = tokenizer.next() {
+            let (offset, event) = result.unwrap();
+
+            match (previous, event) {
+                (Some((1, Event::Bytes(b"info"))), Event::DictStart) => info = Some(offset),
+                (Some((2, Event::Bytes(b"pieces"))), Event::Bytes(bytes)) => {
+                    pieces = Some(bytes.len() / 20)
+                }
+                _ => {}
+            }
+
+            previous = Some((tokenizer.depth(), event));
+        }
+
+        let value = from_slice::<Value>(&f).unwrap();
+        let start = f.windows(6).position(|w| w == b"4:info").unwrap() + 6;
+
+        assert_eq!(Some(start), info);
+        assert_eq!(
+            value["info"]["pieces"].as_bytes().map(|p| p.len() / 20),
+            pieces
+        );
+        assert!(pieces.unwrap() > 0);
+    }
+}
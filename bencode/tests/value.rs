@@ -0,0 +1,241 @@
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::env;
+    use std::fs;
+    use std::path::Path;
+
+    use serde::Serializer;
+    use serde_derive::{Deserialize, Serialize};
+
+    use bitrust_bencode::{
+        bencode, from_slice, from_str, from_value, to_string, to_value, to_vec, Error, ErrorKind,
+        RawValue, Value,
+    };
+
+    #[test]
+    fn scalars() {
+        assert_eq!(Value::Integer(42), from_str::<Value>("i42e").unwrap());
+        assert_eq!(Value::Integer(-42), from_str::<Value>("i-42e").unwrap());
+        assert_eq!(Value::from("spam"), from_str::<Value>("4:spam").unwrap());
+
+        // Byte strings, that are not valid UTF-8, are still accepted.
+        assert_eq!(
+            Value::Bytes(vec![0xf0, 0x9f]),
+            from_slice::<Value>(&[b'2', b':', 0xf0, 0x9f]).unwrap()
+        );
+
+        // Integers beyond `i64` are kept as well.
+        let value = from_str::<Value>(&format!("i{}e", u64::MAX)).unwrap();
+        assert_eq!(Value::from(u64::MAX), value);
+        assert_eq!(Some(u64::MAX), value.as_u64());
+        assert_eq!(None, value.as_int());
+        assert_eq!(format!("i{}e", u64::MAX), to_string(&value).unwrap());
+
+        let value = from_str::<Value>(&format!("i{}e", i128::MIN)).unwrap();
+        assert_eq!(Value::Integer(i128::MIN), value);
+        assert_eq!(format!("i{}e", i128::MIN), to_string(&value).unwrap());
+        assert!(from_str::<Value>(&format!("i{}e", u128::MAX)).is_err());
+    }
+
+    #[test]
+    fn lists_and_dicts() {
+        let value = from_str::<Value>("d3:bar4:spam3:fooli42ei-1eee").unwrap();
+
+        assert_eq!(Some("spam"), value["bar"].as_str());
+        assert_eq!(Some(42), value["foo"][0].as_int());
+        assert_eq!(Some(-1), value[b"foo".as_slice()][1].as_int());
+        assert_eq!(2, value["foo"].as_list().unwrap().len());
+        assert_eq!(2, value.as_dict().unwrap().len());
+
+        assert!(value.get("missing").is_none());
+        assert!(value.get(0).is_none());
+        assert!(value["foo"].get(2).is_none());
+        assert!(value["bar"].get("bar").is_none());
+    }
+
+    #[test]
+    #[should_panic]
+    fn index_missing_key() {
+        let value = from_str::<Value>("d3:bari1ee").unwrap();
+        let _ = &value["foo"];
+    }
+
+    #[test]
+    fn index_mut() {
+        let mut value = from_str::<Value>("d3:barli1eee").unwrap();
+        value["bar"][0] = Value::from("spam");
+        value["bar"].as_list_mut().unwrap().push(Value::Integer(2));
+
+        assert_eq!("d3:barl4:spami2eee", to_string(&value).unwrap());
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut dict = BTreeMap::new();
+        dict.insert(
+            b"list".to_vec(),
+            Value::from(vec![Value::from(1), Value::from("a")]),
+        );
+        dict.insert(b"bytes".to_vec(), Value::from(vec![0u8, 1, 2, 255]));
+        dict.insert(b"int".to_vec(), Value::from(-7));
+        let value = Value::from(dict);
+
+        let encoded = to_vec(&value).unwrap();
+        assert_eq!(
+            b"d5:bytes4:\x00\x01\x02\xff3:inti-7e4:listli1e1:aee".as_slice(),
+            encoded.as_slice()
+        );
+        assert_eq!(value, from_slice::<Value>(&encoded).unwrap());
+    }
+
+    #[test]
+    fn typed_conversions() {
+        #[derive(Serialize, Deserialize, PartialEq, Debug)]
+        struct Info {
+            name: String,
+            length: u64,
+        }
+
+        let info = Info {
+            name: String::from("ubuntu.iso"),
+            length: 1024,
+        };
+
+        let value = to_value(&info).unwrap();
+        assert_eq!(Some("ubuntu.iso"), value["name"].as_str());
+        assert_eq!(Some(1024), value["length"].as_int());
+
+        assert_eq!(info, from_value::<Info>(value).unwrap());
+
+        let length = u64::MAX;
+        assert_eq!(
+            bencode! { "length" => length },
+            to_value(&BTreeMap::from([("length", length)])).unwrap()
+        );
+        assert_eq!(
+            BTreeMap::from([(String::from("length"), length)]),
+            from_value(bencode! { "length" => length }).unwrap()
+        );
+    }
+
+    #[test]
+    fn conversion_rules() {
+        #[derive(Serialize, Deserialize, PartialEq, Debug)]
+        enum Message {
+            Ping,
+            Error(i64, String),
+            Response { id: Vec<u8>, port: Option<u16> },
+        }
+
+        #[derive(Serialize, Deserialize, PartialEq, Debug)]
+        struct Node {
+            #[serde(with = "serde_bytes")]
+            id: Vec<u8>,
+            seed: bool,
+            ratio: f64,
+            peers: BTreeMap<u16, String>,
+            messages: Vec<Message>,
+            comment: Option<String>,
+        }
+
+        struct Duplicates;
+
+        impl serde::Serialize for Duplicates {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.collect_map([("a", 1), ("a", 2)])
+            }
+        }
+
+        let node = Node {
+            id: vec![0xff, 0x00],
+            seed: true,
+            ratio: 1.5,
+            peers: BTreeMap::from([(6881, String::from("a")), (51413, String::from("b"))]),
+            messages: vec![
+                Message::Ping,
+                Message::Error(201, String::from("Generic Error")),
+                Message::Response {
+                    id: vec![1, 2],
+                    port: None,
+                },
+            ],
+            comment: None,
+        };
+
+        // The conversions follow the same rules as the encoding does.
+        let value = to_value(&node).unwrap();
+        assert_eq!(from_slice::<Value>(&to_vec(&node).unwrap()).unwrap(), value);
+        assert_eq!(node, from_value::<Node>(value).unwrap());
+
+        assert!(matches!(
+            to_value(&vec![None::<u8>]).map_err(Error::into_kind),
+            Err(ErrorKind::UnsupportedNone)
+        ));
+        assert!(matches!(
+            to_value(&Duplicates).map_err(Error::into_kind),
+            Err(ErrorKind::DuplicateKey)
+        ));
+        assert!(matches!(
+            from_value::<u8>(Value::from(256)).map_err(Error::into_kind),
+            Err(ErrorKind::IntegerOverflow)
+        ));
+        assert!(matches!(
+            from_value::<BTreeMap<u8, u8>>(bencode! { "1x" => 1 }).map_err(Error::into_kind),
+            Err(ErrorKind::ExpectedInteger)
+        ));
+    }
+
+    #[test]
+    fn raw_values() {
+        #[derive(Serialize)]
+        struct Torrent<'a> {
+            #[serde(borrow)]
+            info: RawValue<'a>,
+        }
+
+        let torrent = Torrent {
+            info: RawValue::from_slice(b"d4:name1:ae").unwrap(),
+        };
+
+        assert_eq!(
+            bencode! { "info" => { "name" => "a" } },
+            to_value(&torrent).unwrap()
+        );
+    }
+
+    #[test]
+    fn deep_nesting() {
+        // The conversions don't go through the encoding, therefore they are
+        // not limited by the nesting depth of the `Deserializer`.
+        let mut value = Value::from(1);
+
+        for _ in 0..200 {
+            value = Value::List(vec![value]);
+        }
+
+        assert_eq!(value, to_value(&value).unwrap());
+        assert_eq!(value, from_value::<Value>(value.clone()).unwrap());
+    }
+
+    #[test]
+    fn value_from_file() {
+        let mut dir = env::current_dir().unwrap();
+        dir.push(Path::new(
+            "tests/data/ubuntu-19.10-desktop-amd64.iso.torrent",
+        ));
+        let f = &fs::read(dir).unwrap();
+
+        let value = from_slice::<Value>(f).unwrap();
+        assert_eq!(
+            Some("ubuntu-19.10-desktop-amd64.iso"),
+            value["info"]["name"].as_str()
+        );
+        assert_eq!(Some(1048576), value["info"]["piece length"].as_int());
+        assert!(value["info"]["pieces"].is_bytes());
+
+        // The file is canonically encoded, therefore it should be encoded
+        // back byte by byte.
+        assert_eq!(f, &to_vec(&value).unwrap());
+    }
+}
@@ -0,0 +1,497 @@
+//! Derive macro of Bencode-specific serialization for Bitrust.
+//!
+//! The macro is re-exported as `bitrust_bencode::Bencode` with the `derive`
+//! feature, the generated code refers to that crate only.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{
+    ext::IdentExt, parse_macro_input, parse_quote, Data, DeriveInput, Error, Fields,
+    GenericArgument, Ident, LitByteStr, LitStr, Path, PathArguments, Result, Type,
+};
+
+/// Derives `Serialize` & `Deserialize` of a struct with named fields, that
+/// is represented as a Bencode dictionary.
+///
+/// The fields are written as the keys of the dictionary, named the same as
+/// the fields, in the canonical order of the keys. Fields of the type
+/// `Option<T>` are optional, they are skipped when `None` & are `None`, when
+/// the key is missing. Any other missing key results in an error, the same as
+/// a duplicate key does. Unknown keys are ignored, unless they are kept by a
+/// `flatten_unknown` field.
+///
+/// The generated code implements the `serde` traits, so the struct can be
+/// used with any format. The fields accept these attributes:
+///
+/// - `#[bencode(rename = "piece length")]` sets the key of the field,
+/// - `#[bencode(bytes)]` encodes a `Vec` of `bytes::Compact` values (e.g. a
+///   `Vec<u8>`) with `bytes::chunks` & any other `Compact` value (e.g. a
+///   `[u8; 20]`) with `bytes::fixed`, i.e. as a byte string instead of a list,
+/// - `#[bencode(int_bool)]` encodes a `bool` as `BoolEncoding::Integer`, i.e.
+///   as the integer `i1e` or `i0e` (as most BEPs do), whatever the options of
+///   the serializer are,
+/// - `#[bencode(flatten_unknown)]` marks a field of the type `Extra`, that
+///   keeps the keys unknown to the struct, & writes them back among the
+///   other fields, in the canonical order.
+///
+/// Unlike with `#[serde(flatten)]`, `serde` doesn't buffer the dictionary of
+/// a struct with a `flatten_unknown` field. The unknown values are read as
+/// `RawValue`s directly, so the `Deserializer` keeps their bytes exactly as
+/// they appeared in the input.
+///
+/// The struct accepts `#[bencode(crate = "path")]`, that sets the path of the
+/// `bitrust_bencode` crate, if it is renamed or re-exported.
+///
+/// Structs with generic parameters are not supported.
+#[proc_macro_derive(Bencode, attributes(bencode))]
+pub fn derive_bencode(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    expand(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// Encoding of a field, selected by its attributes.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Encoding {
+    Default,
+    Bytes,
+    IntBool,
+}
+
+struct Field<'a> {
+    ident: &'a Ident,
+    key: LitStr,
+    ty: &'a Type,
+    /// Type of the value of an `Option` field.
+    optional: Option<&'a Type>,
+    encoding: Encoding,
+    unknown: bool,
+}
+
+impl Field<'_> {
+    /// Type of the value, that is (de)serialized.
+    fn value_type(&self) -> &Type {
+        self.optional.unwrap_or(self.ty)
+    }
+
+    /// Bytes of the key, as they are matched & ordered.
+    fn pattern(&self) -> LitByteStr {
+        LitByteStr::new(self.key.value().as_bytes(), self.key.span())
+    }
+}
+
+/// Returns the path of the `bitrust_bencode` crate.
+fn parse_crate(input: &DeriveInput) -> Result<Path> {
+    let mut path = parse_quote!(::bitrust_bencode);
+
+    for attr in input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("bencode"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("crate") {
+                path = meta.value()?.parse::<LitStr>()?.parse()?;
+                Ok(())
+            } else {
+                Err(meta.error("unknown bencode attribute"))
+            }
+        })?;
+    }
+
+    Ok(path)
+}
+
+fn parse_field(field: &syn::Field) -> Result<Field<'_>> {
+    let ident = field
+        .ident
+        .as_ref()
+        .ok_or_else(|| Error::new_spanned(field, "expected a named field"))?;
+
+    let mut key = LitStr::new(&ident.unraw().to_string(), ident.span());
+    let mut renamed = false;
+    let mut encoding = Encoding::Default;
+    let mut unknown = false;
+
+    for attr in field
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("bencode"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename") {
+                key = meta.value()?.parse()?;
+                renamed = true;
+            } else if meta.path.is_ident("bytes") {
+                encoding = Encoding::Bytes;
+            } else if meta.path.is_ident("int_bool") {
+                encoding = Encoding::IntBool;
+            } else if meta.path.is_ident("flatten_unknown") {
+                unknown = true;
+            } else {
+                return Err(meta.error("unknown bencode attribute"));
+            }
+
+            Ok(())
+        })?;
+    }
+
+    if unknown && (renamed || encoding != Encoding::Default) {
+        return Err(Error::new_spanned(
+            field,
+            "flatten_unknown can not be combined with the other attributes",
+        ));
+    }
+
+    Ok(Field {
+        ident,
+        key,
+        ty: &field.ty,
+        optional: generic_argument(&field.ty, "Option"),
+        encoding,
+        unknown,
+    })
+}
+
+/// Returns `T` of the type `Name<T>`, recognized by its name the same way
+/// `serde` recognizes `Option`.
+fn generic_argument<'a>(ty: &'a Type, name: &str) -> Option<&'a Type> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    let segment = path.path.segments.last()?;
+    let PathArguments::AngleBracketed(arguments) = &segment.arguments else {
+        return None;
+    };
+
+    match arguments.args.first() {
+        Some(GenericArgument::Type(inner))
+            if segment.ident == name && arguments.args.len() == 1 =>
+        {
+            Some(inner)
+        }
+        _ => None,
+    }
+}
+
+fn expand(input: &DeriveInput) -> Result<TokenStream2> {
+    if !input.generics.params.is_empty() {
+        return Err(Error::new_spanned(
+            &input.generics,
+            "#[derive(Bencode)] does not support generic structs",
+        ));
+    }
+
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(Error::new_spanned(
+                    &input.ident,
+                    "#[derive(Bencode)] supports structs with named fields only",
+                ))
+            }
+        },
+        _ => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "#[derive(Bencode)] supports structs only",
+            ))
+        }
+    };
+
+    let krate = parse_crate(input)?;
+    let fields = fields.iter().map(parse_field).collect::<Result<Vec<_>>>()?;
+
+    if let Some(field) = fields.iter().filter(|field| field.unknown).nth(1) {
+        return Err(Error::new_spanned(
+            field.ident,
+            "only a single field can be flatten_unknown",
+        ));
+    }
+
+    for (index, field) in fields.iter().enumerate().filter(|(_, f)| !f.unknown) {
+        if fields[..index]
+            .iter()
+            .any(|other| !other.unknown && other.key.value() == field.key.value())
+        {
+            return Err(Error::new_spanned(&field.key, "duplicate key of a field"));
+        }
+    }
+
+    let serialize = expand_serialize(input, &krate, &fields);
+    let deserialize = expand_deserialize(input, &krate, &fields);
+
+    Ok(quote! {
+        #serialize
+        #deserialize
+    })
+}
+
+/// Returns the `bytes` adapter of a `#[bencode(bytes)]` field.
+fn bytes_adapter(krate: &Path, field: &Field) -> TokenStream2 {
+    if generic_argument(field.value_type(), "Vec").is_some() {
+        quote!(#krate::bytes::chunks)
+    } else {
+        quote!(#krate::bytes::fixed)
+    }
+}
+
+fn expand_serialize(input: &DeriveInput, krate: &Path, fields: &[Field]) -> TokenStream2 {
+    let private = quote!(#krate::__private);
+    let name = &input.ident;
+
+    // The keys are written in the canonical order, whatever the serializer.
+    let mut known = fields
+        .iter()
+        .filter(|field| !field.unknown)
+        .collect::<Vec<_>>();
+    known.sort_by_key(|field| field.key.value());
+
+    let unknown = fields.iter().find(|field| field.unknown);
+
+    let write_unknown = |bound: Option<&LitByteStr>| {
+        let condition = match bound {
+            Some(pattern) => quote!(.next_if(|(key, _)| key.as_slice() < &#pattern[..])),
+            None => quote!(.next()),
+        };
+
+        quote! {
+            while let Some((key, value)) = __unknown #condition {
+                map.serialize_entry(&#private::FieldKey::from(key.as_slice()), value)?;
+            }
+        }
+    };
+
+    let entries = known.iter().map(|field| {
+        let ident = field.ident;
+        let key = &field.key;
+        let ty = field.value_type();
+
+        let (wrapper, value) = match field.encoding {
+            Encoding::Default => (quote!(), quote!(value)),
+            Encoding::Bytes => {
+                let adapter = bytes_adapter(krate, field);
+                let wrapper = quote! {
+                    struct __SerializeWith<'__a>(&'__a #ty);
+
+                    impl #private::serde::Serialize for __SerializeWith<'_> {
+                        fn serialize<__S>(
+                            &self,
+                            serializer: __S,
+                        ) -> ::core::result::Result<__S::Ok, __S::Error>
+                        where
+                            __S: #private::serde::Serializer,
+                        {
+                            #adapter::serialize(self.0, serializer)
+                        }
+                    }
+                };
+
+                (wrapper, quote!(&__SerializeWith(value)))
+            }
+            Encoding::IntBool => (quote!(), quote!(&#private::IntBool(value))),
+        };
+
+        let unknown = unknown.map(|_| write_unknown(Some(&field.pattern())));
+
+        let entry = if field.optional.is_some() {
+            quote! {
+                if let Some(value) = &self.#ident {
+                    map.serialize_entry(#key, #value)?;
+                }
+            }
+        } else {
+            quote! {
+                let value = &self.#ident;
+                map.serialize_entry(#key, #value)?;
+            }
+        };
+
+        quote! {
+            #unknown
+            {
+                #wrapper
+                #entry
+            }
+        }
+    });
+
+    let (start, end) = match unknown {
+        Some(field) => {
+            let ident = field.ident;
+            let rest = write_unknown(None);
+
+            (
+                quote! {
+                    let mut __unknown = ::core::iter::Iterator::peekable(
+                        ::core::iter::IntoIterator::into_iter(&self.#ident),
+                    );
+                },
+                rest,
+            )
+        }
+        None => (quote!(), quote!()),
+    };
+
+    quote! {
+        impl #private::serde::Serialize for #name {
+            fn serialize<S>(&self, serializer: S) -> ::core::result::Result<S::Ok, S::Error>
+            where
+                S: #private::serde::Serializer,
+            {
+                use #private::serde::ser::SerializeMap as _;
+
+                let mut map = serializer.serialize_map(None)?;
+                #start
+                #(#entries)*
+                #end
+                map.end()
+            }
+        }
+    }
+}
+
+fn expand_deserialize(input: &DeriveInput, krate: &Path, fields: &[Field]) -> TokenStream2 {
+    let private = quote!(#krate::__private);
+    let name = &input.ident;
+    let expecting = format!("struct {}", name);
+
+    let slots = fields
+        .iter()
+        .enumerate()
+        .map(|(index, _)| format_ident!("__field{}", index))
+        .collect::<Vec<_>>();
+
+    let declarations = fields.iter().zip(&slots).map(|(field, slot)| {
+        let ty = field.value_type();
+
+        if field.unknown {
+            quote!(let mut #slot = <#ty as ::core::default::Default>::default();)
+        } else {
+            quote!(let mut #slot: ::core::option::Option<#ty> = None;)
+        }
+    });
+
+    let arms = fields
+        .iter()
+        .zip(&slots)
+        .filter(|(field, _)| !field.unknown)
+        .map(|(field, slot)| {
+            let key = &field.key;
+            let pattern = field.pattern();
+            let ty = field.value_type();
+
+            let (wrapper, value) = match field.encoding {
+                Encoding::Default => (quote!(), quote!(map.next_value::<#ty>()?)),
+                Encoding::Bytes => {
+                    let adapter = bytes_adapter(krate, field);
+                    let wrapper = quote! {
+                        struct __DeserializeWith(#ty);
+
+                        impl<'de> #private::serde::Deserialize<'de> for __DeserializeWith {
+                            fn deserialize<__D>(
+                                deserializer: __D,
+                            ) -> ::core::result::Result<Self, __D::Error>
+                            where
+                                __D: #private::serde::Deserializer<'de>,
+                            {
+                                #adapter::deserialize(deserializer).map(__DeserializeWith)
+                            }
+                        }
+                    };
+
+                    (wrapper, quote!(map.next_value::<__DeserializeWith>()?.0))
+                }
+                Encoding::IntBool => (
+                    quote!(),
+                    quote!(map.next_value::<#private::IntBool<#ty>>()?.0),
+                ),
+            };
+
+            quote! {
+                #pattern => {
+                    #wrapper
+
+                    if #slot.is_some() {
+                        return Err(#private::serde::de::Error::duplicate_field(#key));
+                    }
+                    #slot = Some(#value);
+                }
+            }
+        });
+
+    let fallback = match fields.iter().zip(&slots).find(|(field, _)| field.unknown) {
+        Some((_, slot)) => quote! {
+            _ => {
+                #slot.insert(key.into_owned(), map.next_value::<#krate::RawValue>()?);
+            }
+        },
+        None => quote! {
+            _ => {
+                map.next_value::<#private::serde::de::IgnoredAny>()?;
+            }
+        },
+    };
+
+    let initializers = fields.iter().zip(&slots).map(|(field, slot)| {
+        let ident = field.ident;
+        let key = &field.key;
+
+        if field.unknown || field.optional.is_some() {
+            quote!(#ident: #slot)
+        } else {
+            quote! {
+                #ident: match #slot {
+                    Some(value) => value,
+                    None => return Err(#private::serde::de::Error::missing_field(#key)),
+                }
+            }
+        }
+    });
+
+    quote! {
+        impl<'de> #private::serde::Deserialize<'de> for #name {
+            fn deserialize<D>(deserializer: D) -> ::core::result::Result<Self, D::Error>
+            where
+                D: #private::serde::Deserializer<'de>,
+            {
+                struct __Visitor;
+
+                impl<'de> #private::serde::de::Visitor<'de> for __Visitor {
+                    type Value = #name;
+
+                    fn expecting(
+                        &self,
+                        formatter: &mut ::core::fmt::Formatter,
+                    ) -> ::core::fmt::Result {
+                        formatter.write_str(#expecting)
+                    }
+
+                    fn visit_map<A>(self, mut map: A) -> ::core::result::Result<#name, A::Error>
+                    where
+                        A: #private::serde::de::MapAccess<'de>,
+                    {
+                        #(#declarations)*
+
+                        while let Some(key) = map.next_key::<#private::FieldKey>()? {
+                            match key.as_bytes() {
+                                #(#arms)*
+                                #fallback
+                            }
+                        }
+
+                        Ok(#name {
+                            #(#initializers,)*
+                        })
+                    }
+                }
+
+                deserializer.deserialize_map(__Visitor)
+            }
+        }
+    }
+}
@@ -0,0 +1,293 @@
+//! Command-line inspector of Bencode files, such as `.torrent` or
+//! `.fastresume` files.
+
+use std::{
+    env,
+    error::Error,
+    fmt::Write as _,
+    fs,
+    io::{self, Read, Write},
+    iter::Peekable,
+    process::ExitCode,
+    str::Chars,
+};
+
+use bitrust_bencode::{
+    from_slice_strict,
+    json::{from_json, to_json},
+    Document, Node, RawValue,
+};
+use sha1::{Digest, Sha1};
+
+const USAGE: &str = "\
+Usage: bencode <COMMAND> <FILE> [ARGS]
+
+Commands:
+  print <FILE> [PATH]   Pretty-print the value as an indented tree
+  to-json <FILE>        Convert Bencode into JSON
+  from-json <FILE>      Convert JSON into Bencode, written to the standard output
+  check <FILE>          Validate the strict canonical encoding
+  get <FILE> <PATH>     Print the value at the path, e.g. `info.files[0].length`
+  hash <FILE> [PATH]    Print the SHA-1 of the raw bytes of the value at the path
+
+Keys of the PATH containing any of `.[]\"` are quoted, e.g. `info.\"a.b\"`.
+The FILE `-` reads the standard input.";
+
+/// Number of bytes of a binary string shown by `print`.
+const PREVIEW_LENGTH: usize = 20;
+
+type Result<T> = std::result::Result<T, Box<dyn Error>>;
+
+fn main() -> ExitCode {
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    let args = args.iter().map(String::as_str).collect::<Vec<_>>();
+
+    let result = match args.as_slice() {
+        ["print", file] => read(file).and_then(|data| print(&data, "")),
+        ["print", file, path] => read(file).and_then(|data| print(&data, path)),
+        ["get", file, path] => read(file).and_then(|data| get(&data, path)),
+        ["to-json", file] => read(file).and_then(|data| convert_to_json(&data)),
+        ["from-json", file] => read(file).and_then(|data| convert_from_json(&data)),
+        ["check", file] => read(file).and_then(|data| check(&data)),
+        ["hash", file] => read(file).and_then(|data| hash(&data, "")),
+        ["hash", file, path] => read(file).and_then(|data| hash(&data, path)),
+        ["help" | "-h" | "--help"] => {
+            println!("{}", USAGE);
+            return ExitCode::SUCCESS;
+        }
+        _ => {
+            eprintln!("{}", USAGE);
+            return ExitCode::from(2);
+        }
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("bencode: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn read(file: &str) -> Result<Vec<u8>> {
+    if file == "-" {
+        let mut data = Vec::new();
+        io::stdin().read_to_end(&mut data)?;
+
+        Ok(data)
+    } else {
+        fs::read(file).map_err(|e| format!("{}: {}", file, e).into())
+    }
+}
+
+fn print(data: &[u8], path: &str) -> Result<()> {
+    let document = Document::new(data)?;
+
+    let mut output = String::new();
+    write_tree(&mut output, select(&document, path)?, 0);
+    write_output(output.as_bytes())
+}
+
+/// Prints strings & integers as they are, for use in scripts, the other
+/// values as a tree.
+fn get(data: &[u8], path: &str) -> Result<()> {
+    let document = Document::new(data)?;
+    let node = select(&document, path)?;
+
+    match (node.is_int(), node.as_str()) {
+        (true, _) => write_output(integer(node).as_bytes())?,
+        (_, Some(string)) => write_output(string.as_bytes())?,
+        _ => print(data, path)?,
+    }
+
+    Ok(())
+}
+
+fn convert_to_json(data: &[u8]) -> Result<()> {
+    write_output(&serde_json::to_vec_pretty(&to_json(data)?)?)
+}
+
+fn convert_from_json(data: &[u8]) -> Result<()> {
+    let json = serde_json::from_slice::<serde_json::Value>(data)?;
+    let data = from_json(&json)?;
+
+    match io::stdout().write_all(&data) {
+        Err(e) if e.kind() != io::ErrorKind::BrokenPipe => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+fn check(data: &[u8]) -> Result<()> {
+    from_slice_strict::<RawValue>(data)?;
+    write_output(b"ok")
+}
+
+fn hash(data: &[u8], path: &str) -> Result<()> {
+    let document = Document::new(data)?;
+    let digest = Sha1::digest(select(&document, path)?.as_raw());
+    write_output(hex::encode(digest).as_bytes())
+}
+
+/// Writes a line to the standard output, ignoring a closed pipe (e.g. when
+/// piped into `head`).
+fn write_output(line: &[u8]) -> Result<()> {
+    let mut stdout = io::stdout().lock();
+
+    match stdout.write_all(line).and_then(|_| stdout.write_all(b"\n")) {
+        Err(e) if e.kind() != io::ErrorKind::BrokenPipe => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+//////////////////////////////////////////////////////
+
+/// Returns the value at the path, such as `info.files[0]` or `info.files.0`.
+/// An empty path selects the whole document.
+fn select<'d, 'a>(document: &'d Document<'a>, path: &str) -> Result<Node<'d, 'a>> {
+    let mut node = document.root();
+
+    for segment in parse_path(path)? {
+        let found = if node.is_dict() {
+            node.get(&segment)
+        } else if node.is_list() {
+            match segment.parse::<usize>() {
+                Ok(index) => node.at(index),
+                Err(_) => return Err(format!("`{}` is not a list index", segment).into()),
+            }
+        } else {
+            return Err(format!("`{}` is not in a list or dictionary", segment).into());
+        };
+
+        node = found.ok_or_else(|| format!("`{}` not found", segment))?;
+    }
+
+    Ok(node)
+}
+
+/// Splits the path into keys & list indexes, separated by `.` or enclosed
+/// in `[]`. A key containing any of `.[]"` is quoted, e.g. `info."a.b"` or
+/// `info["a.b"]`, with any `"` or `\` in the quotes escaped by a `\`.
+fn parse_path(path: &str) -> Result<Vec<String>> {
+    let mut segments = Vec::new();
+    let mut chars = path.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '.' => {}
+            '[' => {
+                let segment = match chars.peek() {
+                    Some('"') => {
+                        chars.next();
+                        parse_quoted(&mut chars)?
+                    }
+                    _ => take_until(&mut chars, &[']']),
+                };
+
+                if chars.next() != Some(']') {
+                    return Err(format!("missing `]` in the path `{}`", path).into());
+                }
+
+                segments.push(segment);
+            }
+            '"' => segments.push(parse_quoted(&mut chars)?),
+            ']' => return Err(format!("unexpected `]` in the path `{}`", path).into()),
+            c => {
+                let mut segment = String::from(c);
+                segment.push_str(&take_until(&mut chars, &['.', '[', ']', '"']));
+                segments.push(segment);
+            }
+        }
+    }
+
+    Ok(segments)
+}
+
+fn take_until(chars: &mut Peekable<Chars>, delimiters: &[char]) -> String {
+    let mut segment = String::new();
+
+    while let Some(c) = chars.next_if(|c| !delimiters.contains(c)) {
+        segment.push(c);
+    }
+
+    segment
+}
+
+/// Parses a quoted key after the opening quote.
+fn parse_quoted(chars: &mut Peekable<Chars>) -> Result<String> {
+    let mut key = String::new();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => return Ok(key),
+            '\\' => key.extend(chars.next()),
+            c => key.push(c),
+        }
+    }
+
+    Err("unterminated quoted key in the path".into())
+}
+
+//////////////////////////////////////////////////////
+
+/// Returns the number of an integer, exactly as it appears in the input.
+fn integer<'a>(node: Node<'_, 'a>) -> &'a str {
+    let raw = node.as_raw();
+
+    // The number has already been validated while indexing.
+    std::str::from_utf8(&raw[1..raw.len() - 1]).unwrap()
+}
+
+fn write_indent(output: &mut String, depth: usize) {
+    output.extend(std::iter::repeat_n("  ", depth));
+}
+
+fn write_bytes(output: &mut String, bytes: &[u8]) {
+    match std::str::from_utf8(bytes) {
+        Ok(string) => write!(output, "{:?}", string).unwrap(),
+        Err(_) => {
+            let preview = &bytes[..bytes.len().min(PREVIEW_LENGTH)];
+            write!(output, "<{} bytes> {}", bytes.len(), hex::encode(preview)).unwrap();
+
+            if bytes.len() > PREVIEW_LENGTH {
+                output.push_str("...");
+            }
+        }
+    }
+}
+
+/// Writes the value as an indented tree, with one list element or dictionary
+/// entry per line, in the order of the input.
+fn write_tree(output: &mut String, node: Node, depth: usize) {
+    if node.is_int() {
+        output.push_str(integer(node));
+    } else if let Some(bytes) = node.as_bytes() {
+        write_bytes(output, bytes);
+    } else if node.is_empty() {
+        output.push_str(if node.is_list() { "[]" } else { "{}" });
+    } else if node.is_list() {
+        output.push_str("[\n");
+
+        for element in node.iter() {
+            write_indent(output, depth + 1);
+            write_tree(output, element, depth + 1);
+            output.push('\n');
+        }
+
+        write_indent(output, depth);
+        output.push(']');
+    } else {
+        output.push_str("{\n");
+
+        for (key, value) in node.entries() {
+            write_indent(output, depth + 1);
+            write_bytes(output, key);
+            output.push_str(": ");
+            write_tree(output, value, depth + 1);
+            output.push('\n');
+        }
+
+        write_indent(output, depth);
+        output.push('}');
+    }
+}
@@ -0,0 +1,140 @@
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+    use std::process::{Command, Output, Stdio};
+
+    const TORRENT: &str = "../bencode/tests/data/ubuntu-19.10-desktop-amd64.iso.torrent";
+
+    fn bencode(args: &[&str], input: Option<&[u8]>) -> Output {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_bencode"))
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+
+        let mut stdin = child.stdin.take().unwrap();
+        stdin.write_all(input.unwrap_or_default()).unwrap();
+        drop(stdin);
+
+        child.wait_with_output().unwrap()
+    }
+
+    fn stdout(args: &[&str]) -> String {
+        let output = bencode(args, None);
+        assert!(output.status.success(), "{:?}", output);
+
+        String::from_utf8(output.stdout).unwrap()
+    }
+
+    #[test]
+    fn print() {
+        let tree = stdout(&["print", TORRENT]);
+
+        assert!(tree.starts_with("{\n  \"announce\": \"https://torrent.ubuntu.com/announce\"\n"));
+        assert!(tree.contains("\n    \"piece length\": 1048576\n"));
+        assert!(tree.contains("\n    \"pieces\": <47000 bytes> 9bd5fdf7"));
+
+        assert_eq!(
+            "[\n  \"https://ipv6.torrent.ubuntu.com/announce\"\n]\n",
+            stdout(&["print", TORRENT, "announce-list[1]"])
+        );
+    }
+
+    #[test]
+    fn get() {
+        assert_eq!(
+            "ubuntu-19.10-desktop-amd64.iso\n",
+            stdout(&["get", TORRENT, "info.name"])
+        );
+        assert_eq!("1048576\n", stdout(&["get", TORRENT, "info.piece length"]));
+        assert_eq!(
+            "https://ipv6.torrent.ubuntu.com/announce\n",
+            stdout(&["get", TORRENT, "announce-list.1.0"])
+        );
+
+        let output = bencode(&["get", TORRENT, "info.files"], None);
+        assert!(!output.status.success());
+        assert_eq!(
+            "bencode: `files` not found\n",
+            String::from_utf8(output.stderr).unwrap()
+        );
+    }
+
+    #[test]
+    fn paths() {
+        let data = b"d3:a.bd1:cli7eee3:x\"yi2ee";
+        let get = |path| {
+            let output = bencode(&["get", "-", path], Some(data));
+            let stream = match output.status.success() {
+                true => output.stdout,
+                false => output.stderr,
+            };
+
+            String::from_utf8(stream).unwrap()
+        };
+
+        // Keys containing any of `.[]"` are quoted.
+        assert_eq!("7\n", get(r#""a.b".c[0]"#));
+        assert_eq!("7\n", get(r#"["a.b"]["c"].0"#));
+        assert_eq!("2\n", get(r#""x\"y""#));
+
+        assert_eq!("bencode: unexpected `]` in the path `a]`\n", get("a]"));
+        assert_eq!(
+            "bencode: unterminated quoted key in the path\n",
+            get(r#""a.b"#)
+        );
+        assert_eq!(
+            "bencode: missing `]` in the path `[\"a.b\"`\n",
+            get(r#"["a.b""#)
+        );
+        assert_eq!("bencode: missing `]` in the path `[0`\n", get("[0"));
+    }
+
+    #[test]
+    fn trailing_data() {
+        // The whole document is checked, not only the selected value.
+        for path in ["", "a"] {
+            let output = bencode(&["get", "-", path], Some(b"d1:ai1eei2e"));
+            assert!(!output.status.success());
+            assert_eq!(
+                "bencode: Trailing characters at byte 8\n",
+                String::from_utf8(output.stderr).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn hash() {
+        // The info-hash of the torrent.
+        assert_eq!(
+            "e2467cbf021192c241367b892230dc1e05c0580e\n",
+            stdout(&["hash", TORRENT, "info"])
+        );
+    }
+
+    #[test]
+    fn json() {
+        let json = stdout(&["to-json", TORRENT]);
+        assert!(json.contains("\"$hex\": \"9bd5fdf7"));
+
+        let output = bencode(&["from-json", "-"], Some(json.as_bytes()));
+        assert!(output.status.success());
+        assert_eq!(std::fs::read(TORRENT).unwrap(), output.stdout);
+    }
+
+    #[test]
+    fn check() {
+        assert_eq!("ok\n", stdout(&["check", TORRENT]));
+
+        let output = bencode(&["check", "-"], Some(b"d1:bi1e1:ai2ee"));
+        assert!(!output.status.success());
+        assert!(String::from_utf8(output.stderr)
+            .unwrap()
+            .contains("at byte 7"));
+
+        let output = bencode(&["unknown"], None);
+        assert_eq!(Some(2), output.status.code());
+    }
+}